@@ -1,10 +1,11 @@
 use bevy::{audio::Volume, prelude::*};
 
+pub mod adaptive;
+pub mod mixer;
+pub mod stereo;
+
 pub fn plugin(app: &mut App) {
-    app.add_systems(
-        Update,
-        apply_global_volume.run_if(resource_changed::<GlobalVolume>),
-    );
+    app.add_plugins((adaptive::plugin, mixer::plugin, stereo::plugin));
 }
 
 /// An organizational marker component that should be added to a spawned [`AudioPlayer`] if it's in the
@@ -39,13 +40,3 @@ pub fn sound_effect(handle: Handle<AudioSource>, volume: f32) -> impl Bundle {
         SoundEffect,
     )
 }
-
-/// [`GlobalVolume`] doesn't apply to already-running audio entities, so this system will update them.
-fn apply_global_volume(
-    global_volume: Res<GlobalVolume>,
-    mut audio_query: Query<(&PlaybackSettings, &mut AudioSink)>,
-) {
-    for (playback, mut sink) in &mut audio_query {
-        sink.set_volume(global_volume.volume * playback.volume);
-    }
-}
@@ -0,0 +1,277 @@
+//! Music that follows how much trouble the player is in.
+//!
+//! The gameplay music is made of [`MusicLayer`]s that all play at once and crossfade with the
+//! [`Danger`]: the more wolves, the closer the nearest one, and the longer the run, the more of
+//! the urgent layers come in. Stingers mark the moments the player should notice, jumping to
+//! another sheep and a wolf's charge just missing them.
+
+use bevy::{audio::Volume, ecs::spawn::SpawnIter, prelude::*};
+
+use crate::{
+    AppSystems, PausableSystems,
+    audio::{Music, mixer},
+    demo::{
+        level::LevelAssets,
+        movement::HumanMind,
+        player::Jumped,
+        wolf::{DifficultySecs, NearMiss, Wolf},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Danger>();
+    app.add_systems(OnEnter(Screen::Gameplay), reset);
+    app.add_systems(
+        Update,
+        (
+            update_danger.in_set(PausableSystems),
+            crossfade,
+            stingers.run_if(resource_exists::<LevelAssets>),
+        )
+            .chain()
+            .after(AppSystems::Update)
+            .before(mixer::apply_volume),
+    );
+}
+
+/// How bad things are for the player
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct Danger {
+    /// From 0 to 1, what the music follows
+    pub level: f32,
+    /// Where `level` is heading, from what's on the field right now
+    pub target: f32,
+}
+
+/// More wolves than this don't make it worse
+const WOLVES_FELT: f32 = 4.;
+/// The nearest wolf starts to count from this far away
+const FAR: f32 = 250.;
+/// And counts fully from this close
+const NEAR: f32 = 40.;
+/// Surviving longer than this doesn't make it worse
+const SURVIVED_FELT_SECS: f32 = 180.;
+/// The music gets tense quickly, but takes its time to calm down
+const RISE_PER_SEC: f32 = 1.5;
+const FALL_PER_SEC: f32 = 0.25;
+
+/// From 0 to 1, with the distance to the nearest wolf weighing the most
+pub fn danger(wolves: usize, nearest: Option<f32>, survived_secs: f32) -> f32 {
+    let crowd = (wolves as f32 / WOLVES_FELT).min(1.);
+    let close = nearest.map_or(0., |dist| 1. - ((dist - NEAR) / (FAR - NEAR)).clamp(0., 1.));
+    let time = (survived_secs / SURVIVED_FELT_SECS).min(1.);
+    0.3f32.mul_add(crowd, 0.5f32.mul_add(close, 0.2 * time))
+}
+
+fn reset(mut danger: ResMut<Danger>) {
+    *danger = Danger::default();
+}
+
+fn update_danger(
+    time: Res<Time>,
+    screen: Res<State<Screen>>,
+    dif: Res<DifficultySecs>,
+    player: Query<&Transform, With<HumanMind>>,
+    wolves: Query<&Transform, With<Wolf>>,
+    mut danger: ResMut<Danger>,
+) {
+    danger.target = match (**screen, player.iter().next()) {
+        (Screen::Gameplay, Some(player)) => {
+            let player = player.translation.xy();
+            let nearest = wolves
+                .iter()
+                .map(|wolf| wolf.translation.xy().distance(player))
+                .min_by(f32::total_cmp);
+            self::danger(wolves.iter().count(), nearest, dif.0)
+        }
+        _ => 0.,
+    };
+
+    let rate = if danger.target > danger.level {
+        RISE_PER_SEC
+    } else {
+        FALL_PER_SEC
+    };
+    let step = rate * time.delta_secs();
+    danger.level += (danger.target - danger.level).clamp(-step, step);
+}
+
+/// One part of the gameplay music
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum MusicLayer {
+    /// Always there, but ducks under the others once things are bad
+    Calm,
+    Tense,
+    /// A wolf is right there
+    Chase,
+}
+
+const LAYERS: [MusicLayer; 3] = [MusicLayer::Calm, MusicLayer::Tense, MusicLayer::Chase];
+
+impl MusicLayer {
+    /// Until there are proper stems, every layer is the same loop played faster, which is also
+    /// higher and more urgent
+    const fn speed(self) -> f32 {
+        match self {
+            Self::Calm => 1.,
+            // A fifth up
+            Self::Tense => 1.5,
+            // An octave up
+            Self::Chase => 2.,
+        }
+    }
+
+    /// The volume of the layer at some level of [`Danger`]
+    pub fn gain(self, danger: f32) -> f32 {
+        match self {
+            Self::Calm => 0.7f32.mul_add(-smoothstep(0.4, 0.8, danger), 1.),
+            Self::Tense => 0.6 * smoothstep(0.2, 0.5, danger),
+            Self::Chase => 0.5 * smoothstep(0.6, 0.9, danger),
+        }
+    }
+}
+
+fn smoothstep(from: f32, to: f32, x: f32) -> f32 {
+    let t = ((x - from) / (to - from)).clamp(0., 1.);
+    t * t * 2f32.mul_add(-t, 3.)
+}
+
+/// Every [`MusicLayer`] as children, starting out calm
+pub fn layers(music: &Handle<AudioSource>) -> impl Bundle {
+    let music = music.clone();
+    Children::spawn(SpawnIter(LAYERS.into_iter().map(move |layer| {
+        (
+            Name::new(format!("{layer:?} Music")),
+            layer,
+            AudioPlayer(music.clone()),
+            PlaybackSettings {
+                speed: layer.speed(),
+                volume: Volume::Linear(layer.gain(0.)),
+                ..PlaybackSettings::LOOP
+            },
+            Music,
+        )
+    })))
+}
+
+/// Sets the layers' volume, the [`mixer`] passes it on to what's playing
+fn crossfade(danger: Res<Danger>, layers: Query<(&MusicLayer, &mut PlaybackSettings)>) {
+    if !danger.is_changed() {
+        return;
+    }
+    for (layer, mut playback) in layers {
+        playback.volume = Volume::Linear(layer.gain(danger.level));
+    }
+}
+
+/// A short cue over the music for a moment the player should notice
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct Stinger;
+
+const STINGER_VOLUME: f32 = 0.4;
+
+fn stingers(
+    mut commands: Commands,
+    assets: Res<LevelAssets>,
+    mut jumped: MessageReader<Jumped>,
+    mut near_miss: MessageReader<NearMiss>,
+) {
+    let stingers = [
+        (jumped.read().count() > 0, &assets.jump_stinger, 1.5),
+        (near_miss.read().count() > 0, &assets.near_miss_stinger, 0.6),
+    ];
+    for (_, handle, speed) in stingers.into_iter().filter(|stinger| stinger.0) {
+        commands.spawn((
+            Name::new("Stinger"),
+            Stinger,
+            AudioPlayer(handle.clone()),
+            PlaybackSettings {
+                speed,
+                volume: Volume::Linear(STINGER_VOLUME),
+                ..PlaybackSettings::DESPAWN
+            },
+            Music,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::uuid_handle;
+
+    use super::*;
+
+    #[test]
+    fn danger_grows_with_every_threat() {
+        let calm = danger(0, None, 0.);
+        assert!(calm.abs() < 1e-6);
+        assert!(danger(2, None, 0.) > calm);
+        assert!(danger(2, Some(100.), 0.) > danger(2, Some(200.), 0.));
+        assert!(danger(2, Some(100.), 60.) > danger(2, Some(100.), 0.));
+
+        let worst = danger(10, Some(0.), 1000.);
+        assert!((worst - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn layers_come_in_with_danger() {
+        let gains = |danger| LAYERS.map(|layer| layer.gain(danger));
+        let calm = gains(0.);
+        assert!((calm[0] - 1.).abs() < 1e-6 && calm[1] < 1e-6 && calm[2] < 1e-6);
+
+        let tense = gains(0.5);
+        assert!(tense[1] > 0. && tense[2] < 1e-6);
+
+        let chase = gains(1.);
+        assert!(chase[0] < chase[1] && chase[2] > 0.);
+    }
+
+    #[test]
+    fn layers_cross_over_as_danger_rises() {
+        let gain = MusicLayer::gain;
+        assert!(gain(MusicLayer::Calm, 0.3) > gain(MusicLayer::Tense, 0.3));
+        assert!(gain(MusicLayer::Tense, 0.8) > gain(MusicLayer::Calm, 0.8));
+        assert!(gain(MusicLayer::Chase, 0.5) < gain(MusicLayer::Calm, 0.5));
+        assert!(gain(MusicLayer::Chase, 1.) > gain(MusicLayer::Calm, 1.));
+    }
+
+    #[test]
+    fn jumps_and_near_misses_play_stingers() {
+        let mut app = App::new();
+        app.add_message::<Jumped>();
+        app.add_message::<NearMiss>();
+        let jump = uuid_handle!("4c4e6a4e-2b1d-4a52-9a53-6a5f0c1e7d01");
+        let near_miss = uuid_handle!("4c4e6a4e-2b1d-4a52-9a53-6a5f0c1e7d02");
+        app.insert_resource(LevelAssets {
+            music: Handle::default(),
+            jump_stinger: jump.clone(),
+            near_miss_stinger: near_miss.clone(),
+        });
+        app.add_systems(Update, stingers);
+
+        let played = |app: &mut App| {
+            let mut query = app
+                .world_mut()
+                .query_filtered::<&AudioPlayer, With<Stinger>>();
+            query
+                .iter(app.world())
+                .map(|player| player.0.id())
+                .collect::<Vec<_>>()
+        };
+        app.update();
+        assert!(played(&mut app).is_empty());
+
+        app.world_mut().write_message(Jumped);
+        app.update();
+        assert_eq!(played(&mut app), [jump.id()]);
+
+        app.world_mut().write_message(NearMiss);
+        app.update();
+        assert_eq!(played(&mut app).len(), 2);
+        assert!(played(&mut app).contains(&near_miss.id()));
+    }
+}
@@ -0,0 +1,355 @@
+//! Mix the game's sounds.
+//!
+//! Every [`Music`] and [`SoundEffect`] goes through a [`Bus`] with its own volume, on top of the
+//! [`GlobalVolume`]. Sounds in a [`SoundGroup`] also get a slightly different pitch and volume
+//! each time, and only so many of them play at once, so a flock bleating together sounds like a
+//! chorus instead of one loud clipped bleat.
+
+use bevy::{audio::Volume, prelude::*};
+use rand::Rng;
+
+use crate::{
+    AppSystems,
+    audio::{Music, SoundEffect},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Mixer>();
+    app.add_observer(hold_until_mixed);
+    app.add_systems(
+        Update,
+        (vary, limit_voices, apply_volume)
+            .chain()
+            // Sounds spawned by the game are there by now
+            .after(AppSystems::Update),
+    );
+}
+
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Mixer {
+    pub music: Bus,
+    pub sound_effects: Bus,
+    pub bleats: GroupMix,
+    pub bites: GroupMix,
+    pub interface: GroupMix,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self {
+            music: Bus::default(),
+            sound_effects: Bus::default(),
+            bleats: GroupMix {
+                max_voices: 5,
+                policy: VoicePolicy::DropQuietest,
+                pitch_jitter: 0.08,
+                volume_jitter: 0.2,
+            },
+            bites: GroupMix {
+                max_voices: 3,
+                policy: VoicePolicy::StealOldest,
+                pitch_jitter: 0.05,
+                volume_jitter: 0.1,
+            },
+            interface: GroupMix {
+                max_voices: 2,
+                policy: VoicePolicy::StealOldest,
+                pitch_jitter: 0.,
+                volume_jitter: 0.,
+            },
+        }
+    }
+}
+
+impl Mixer {
+    const fn group(&self, group: SoundGroup) -> &GroupMix {
+        match group {
+            SoundGroup::Bleat => &self.bleats,
+            SoundGroup::Bite => &self.bites,
+            SoundGroup::Interface => &self.interface,
+        }
+    }
+}
+
+/// The volume for a whole category of sounds
+#[derive(Reflect, Debug, Clone, Copy)]
+pub struct Bus {
+    /// From 0 to 1
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self {
+            volume: 1.,
+            muted: false,
+        }
+    }
+}
+
+impl Bus {
+    const fn gain(self) -> f32 {
+        if self.muted { 0. } else { self.volume }
+    }
+}
+
+/// Sounds that are limited and varied together, see [`GroupMix`]
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum SoundGroup {
+    Bleat,
+    Bite,
+    /// Menu buttons
+    Interface,
+}
+
+const SOUND_GROUPS: [SoundGroup; 3] = [SoundGroup::Bleat, SoundGroup::Bite, SoundGroup::Interface];
+
+#[derive(Reflect, Debug, Clone, Copy)]
+pub struct GroupMix {
+    /// How many sounds of the group can play at once
+    pub max_voices: usize,
+    /// Which one gives way when there are too many
+    pub policy: VoicePolicy,
+    /// The pitch goes up or down by up to this much, as a fraction
+    pub pitch_jitter: f32,
+    /// Same for the volume
+    pub volume_jitter: f32,
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoicePolicy {
+    /// Cut off the sound that's been playing the longest
+    StealOldest,
+    /// Drop the quietest sound, which may be the new one
+    DropQuietest,
+}
+
+/// When a sound in a [`SoundGroup`] was started, to tell which one is the oldest
+#[derive(Component, Debug)]
+pub(super) struct Started(u64);
+
+fn vary(
+    mut commands: Commands,
+    mixer: Res<Mixer>,
+    new: Query<(Entity, &SoundGroup, &mut PlaybackSettings), Without<Started>>,
+    mut started: Local<u64>,
+) {
+    // Only the sound changes, so this doesn't need the game's seeded rng
+    let rng = &mut rand::rng();
+    for (id, group, mut playback) in new {
+        let mix = mixer.group(*group);
+        playback.speed *= 1. + rng.random_range(-mix.pitch_jitter..=mix.pitch_jitter);
+        playback.volume *=
+            Volume::Linear(1. + rng.random_range(-mix.volume_jitter..=mix.volume_jitter));
+
+        *started += 1;
+        commands.entity(id).insert(Started(*started));
+    }
+}
+
+pub(super) fn limit_voices(
+    mut commands: Commands,
+    mixer: Res<Mixer>,
+    voices: Query<(Entity, &SoundGroup, &Started, &PlaybackSettings)>,
+) {
+    for group in SOUND_GROUPS {
+        let mix = mixer.group(group);
+        let mut playing: Vec<_> = voices.iter().filter(|voice| *voice.1 == group).collect();
+        let extra = playing.len().saturating_sub(mix.max_voices);
+        if extra == 0 {
+            continue;
+        }
+
+        match mix.policy {
+            VoicePolicy::StealOldest => playing.sort_by_key(|voice| voice.2.0),
+            VoicePolicy::DropQuietest => playing.sort_by(|a, b| {
+                let (a_volume, b_volume) = (a.3.volume.to_linear(), b.3.volume.to_linear());
+                // Rather not cut off a sound that's already playing
+                a_volume.total_cmp(&b_volume).then(b.2.0.cmp(&a.2.0))
+            }),
+        }
+        for (id, ..) in playing.into_iter().take(extra) {
+            commands.entity(id).despawn();
+        }
+    }
+}
+
+/// A new sound that starts paused, until [`apply_volume`] has put it through its [`Bus`]
+#[derive(Component, Debug)]
+pub(super) struct Unmixed;
+
+/// Bevy starts playing a sound as soon as it's spawned, before the [`Mixer`] has had a say, so a
+/// muted bus would still let every new sound through for a moment
+fn hold_until_mixed(
+    add: On<Add, PlaybackSettings>,
+    mut commands: Commands,
+    mut sounds: Query<&mut PlaybackSettings, Or<(With<Music>, With<SoundEffect>)>>,
+) {
+    let Ok(mut playback) = sounds.get_mut(add.entity) else {
+        return;
+    };
+    // Paused on purpose, leave it to whoever spawned it
+    if playback.paused {
+        return;
+    }
+    playback.paused = true;
+    commands.entity(add.entity).insert(Unmixed);
+}
+
+/// [`GlobalVolume`] doesn't apply to already-running audio entities, and Bevy doesn't know about
+/// the [`Mixer`] at all, so this system sets the volume of new sounds and updates the rest, along
+/// with any sound whose [`PlaybackSettings::volume`] changed while it plays.
+pub(super) fn apply_volume(
+    mut commands: Commands,
+    global_volume: Res<GlobalVolume>,
+    mixer: Res<Mixer>,
+    sinks: Query<(
+        Entity,
+        Ref<PlaybackSettings>,
+        &mut AudioSink,
+        Has<Music>,
+        Has<SoundEffect>,
+        Has<Unmixed>,
+    )>,
+    spatial_sinks: Query<(
+        Entity,
+        Ref<PlaybackSettings>,
+        &mut SpatialAudioSink,
+        Has<Music>,
+        Has<SoundEffect>,
+        Has<Unmixed>,
+    )>,
+) {
+    let changed = global_volume.is_changed() || mixer.is_changed();
+    let volume = |playback: &PlaybackSettings, music: bool, sound_effect: bool| {
+        let bus = if music {
+            mixer.music.gain()
+        } else if sound_effect {
+            mixer.sound_effects.gain()
+        } else {
+            1.
+        };
+        global_volume.volume * Volume::Linear(bus) * playback.volume
+    };
+
+    for (id, playback, mut sink, music, sound_effect, unmixed) in sinks {
+        if changed || sink.is_added() || playback.is_changed() {
+            sink.set_volume(volume(&playback, music, sound_effect));
+        }
+        if unmixed {
+            sink.play();
+            commands.entity(id).remove::<Unmixed>();
+        }
+    }
+    for (id, playback, mut sink, music, sound_effect, unmixed) in spatial_sinks {
+        if changed || sink.is_added() || playback.is_changed() {
+            sink.set_volume(volume(&playback, music, sound_effect));
+        }
+        if unmixed {
+            sink.play();
+            commands.entity(id).remove::<Unmixed>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(policy: VoicePolicy) -> App {
+        let mut app = App::new();
+        app.insert_resource(Mixer {
+            bleats: GroupMix {
+                max_voices: 3,
+                policy,
+                pitch_jitter: 0.,
+                volume_jitter: 0.,
+            },
+            ..default()
+        });
+        app.add_systems(Update, (vary, limit_voices).chain());
+        app
+    }
+
+    fn bleat(app: &mut App, volume: f32) -> Entity {
+        let playback = PlaybackSettings {
+            volume: Volume::Linear(volume),
+            ..PlaybackSettings::DESPAWN
+        };
+        app.world_mut().spawn((playback, SoundGroup::Bleat)).id()
+    }
+
+    fn alive(app: &App, ids: &[Entity]) -> Vec<bool> {
+        ids.iter()
+            .map(|id| app.world().get_entity(*id).is_ok())
+            .collect()
+    }
+
+    #[test]
+    fn muted_sounds_start_muted() {
+        let mut app = App::new();
+        app.init_resource::<GlobalVolume>();
+        app.insert_resource(Mixer {
+            sound_effects: Bus {
+                volume: 1.,
+                muted: true,
+            },
+            ..default()
+        });
+        app.add_observer(hold_until_mixed);
+        app.add_systems(Update, apply_volume);
+
+        let id = app
+            .world_mut()
+            .spawn((PlaybackSettings::DESPAWN, SoundEffect))
+            .id();
+        let playback = app.world().get::<PlaybackSettings>(id).unwrap();
+        assert!(playback.paused);
+        let paused_on_purpose = app
+            .world_mut()
+            .spawn((PlaybackSettings::LOOP.paused(), Music))
+            .id();
+
+        // What Bevy does with a new sound, before the mixer gets to it
+        let (sink, _output) = rodio::Sink::new_idle();
+        sink.pause();
+        app.world_mut().entity_mut(id).insert(AudioSink::new(sink));
+        app.update();
+
+        let sink = app.world().get::<AudioSink>(id).unwrap();
+        assert!(!sink.is_paused());
+        assert!(sink.volume().to_linear() < 1e-6);
+        assert!(app.world().get::<Unmixed>(id).is_none());
+        assert!(app.world().get::<Unmixed>(paused_on_purpose).is_none());
+    }
+
+    #[test]
+    fn oldest_voices_are_stolen() {
+        let mut app = app(VoicePolicy::StealOldest);
+        let old: Vec<_> = (0..3).map(|_| bleat(&mut app, 1.)).collect();
+        app.update();
+        assert_eq!(alive(&app, &old), [true, true, true]);
+
+        let new = [bleat(&mut app, 0.1), bleat(&mut app, 0.1)];
+        app.update();
+        assert_eq!(alive(&app, &old), [false, false, true]);
+        assert_eq!(alive(&app, &new), [true, true]);
+    }
+
+    #[test]
+    fn quietest_voices_are_dropped() {
+        let mut app = app(VoicePolicy::DropQuietest);
+        let old: Vec<_> = [0.5, 0.2, 0.8]
+            .map(|volume| bleat(&mut app, volume))
+            .to_vec();
+        app.update();
+
+        let new = [bleat(&mut app, 0.1), bleat(&mut app, 0.9)];
+        app.update();
+        assert_eq!(alive(&app, &old), [true, false, true]);
+        assert_eq!(alive(&app, &new), [false, true]);
+    }
+}
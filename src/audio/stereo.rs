@@ -0,0 +1,138 @@
+//! Place sounds in the stereo field, so the player can hear where they came from.
+//!
+//! A [`Positional`] sound is panned by its x position on the screen, and gets quieter the further
+//! it is from the player's sheep. The panning uses Bevy's spatial audio with the sound placed
+//! between the ears, close enough that only the left/right balance changes, and the distance is
+//! left to [`Stereo`].
+
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{AppSystems, audio::mixer, camera::GAME_WIDTH, demo::movement::HumanMind};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Stereo>();
+    app.add_systems(Startup, spawn_ears);
+    app.add_systems(
+        Update,
+        place
+            .after(AppSystems::Update)
+            // Far away sounds are the quiet ones that get dropped
+            .before(mixer::limit_voices),
+    );
+}
+
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Stereo {
+    /// From 0 for mono, to 1 for sounds on the edges of the screen panned as far as they go
+    pub width: f32,
+    /// Sounds this far from the player's sheep are at half volume
+    pub half_volume_dist: f32,
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Self {
+            width: 1.,
+            half_volume_dist: 150.,
+        }
+    }
+}
+
+/// Where in the world a sound comes from
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+#[require(Transform)]
+pub struct Positional(pub Vec2);
+
+/// Sounds between the ears are never further than this from either, so the volume doesn't
+/// depend on the distance to them
+const EAR_GAP: f32 = 1.;
+
+fn spawn_ears(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Ears"),
+        SpatialListener::new(EAR_GAP),
+        Transform::default(),
+    ));
+}
+
+fn place(
+    stereo: Res<Stereo>,
+    player: Query<&Transform, (With<HumanMind>, Without<Positional>)>,
+    new: Query<(&Positional, &mut PlaybackSettings, &mut Transform), Added<Positional>>,
+) {
+    let player = player.iter().next().map(|transf| transf.translation.xy());
+    for (&Positional(pos), mut playback, mut transf) in new {
+        let pan = (2. * pos.x / GAME_WIDTH).clamp(-1., 1.) * stereo.width;
+        // rodio 0.20's spatial source turns up the ear that's further away, so a sound that
+        // should come from the left goes nearer the right ear
+        *transf = Transform::from_xyz(-pan * EAR_GAP / 2., 0., 0.);
+        playback.spatial = true;
+
+        if let Some(player) = player {
+            let dist = pos.distance(player);
+            playback.volume *=
+                Volume::Linear(stereo.half_volume_dist / (stereo.half_volume_dist + dist));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rodio::{buffer::SamplesBuffer, source::Spatial};
+
+    use super::*;
+
+    /// The left and right volume of a sound at `emitter`, played through rodio's own spatial
+    /// source like Bevy does
+    fn channel_gains(emitter: Vec3, listener: &SpatialListener) -> (f32, f32) {
+        let sample = SamplesBuffer::new(1, 44_100, vec![1f32]);
+        let mut spatial = Spatial::new(
+            sample,
+            emitter.to_array(),
+            listener.left_ear_offset.to_array(),
+            listener.right_ear_offset.to_array(),
+        );
+        (spatial.next().unwrap(), spatial.next().unwrap())
+    }
+
+    fn sound(app: &mut App, pos: Vec2) -> Entity {
+        let playback = PlaybackSettings::DESPAWN;
+        app.world_mut().spawn((playback, Positional(pos))).id()
+    }
+
+    #[test]
+    fn sounds_are_panned_and_fade_with_distance() {
+        let mut app = App::new();
+        app.init_resource::<Stereo>();
+        app.add_systems(Update, place);
+        app.world_mut()
+            .spawn((HumanMind::default(), Transform::default()));
+        let left = sound(&mut app, Vec2::new(-GAME_WIDTH / 4., 0.));
+        let far_right = sound(&mut app, Vec2::new(GAME_WIDTH, 0.));
+        let middle = sound(&mut app, Vec2::new(0., 100.));
+        app.update();
+
+        let world = app.world();
+        let listener = SpatialListener::new(EAR_GAP);
+        let gains = |id| channel_gains(world.get::<Transform>(id).unwrap().translation, &listener);
+        let volume = |id| {
+            world
+                .get::<PlaybackSettings>(id)
+                .unwrap()
+                .volume
+                .to_linear()
+        };
+        let (left_l, left_r) = gains(left);
+        assert!(left_l > left_r);
+        let (right_l, right_r) = gains(far_right);
+        assert!((right_r - 1.).abs() < 1e-6 && right_r > left_l);
+        assert!((right_l - 0.5).abs() < 1e-6);
+        let (middle_l, middle_r) = gains(middle);
+        assert!((middle_l - middle_r).abs() < 1e-6);
+
+        assert!(volume(far_right) < volume(left));
+        assert!(volume(left) < 1.);
+    }
+}
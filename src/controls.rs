@@ -1,5 +1,5 @@
 use bevy::{
-    input::{ButtonState, keyboard::KeyboardInput},
+    input::{ButtonState, gamepad::GamepadButtonStateChangedEvent, keyboard::KeyboardInput},
     prelude::*,
 };
 
@@ -7,18 +7,26 @@ use crate::{
     AppSystems, PausableSystems,
     menus::{
         ControlScheme,
-        PlayerAction::{self, Bleat, Down, Left, Right, Up},
+        PlayerAction::{self, Assist, Bleat, Boost, Down, Jump, Left, Right, Up},
     },
+    replay::Replay,
 };
 
+pub mod pointer;
+
 pub fn plugin(app: &mut App) {
     app.init_resource::<PlayerInput>();
+    app.init_resource::<Latched>();
+    app.init_resource::<ControlScheme>();
     app.add_systems(
         Update,
-        record_input
+        (record_input, record_gamepad)
+            .run_if(not(resource_exists::<Replay>))
             .in_set(AppSystems::RecordInput)
             .in_set(PausableSystems),
     );
+    app.add_systems(FixedPostUpdate, consume_presses);
+    app.add_plugins(pointer::plugin);
 }
 
 #[derive(Resource, Reflect, Debug, Default)]
@@ -30,45 +38,138 @@ pub struct PlayerInput {
     pub left: bool,
     pub right: bool,
     pub bleat: bool,
+    pub jump: bool,
+    pub assist: bool,
+    pub boost: bool,
+    /// The left stick, past the deadzone. Zero when it's not in use.
+    pub stick: Vec2,
 }
 
 impl PlayerInput {
-    const fn get(&self, key: PlayerAction) -> bool {
+    #[must_use]
+    pub const fn get(&self, key: PlayerAction) -> bool {
         match key {
             Up => self.up,
             Down => self.down,
             Left => self.left,
             Right => self.right,
             Bleat => self.bleat,
+            Jump => self.jump,
+            Assist => self.assist,
+            Boost => self.boost,
         }
     }
 
-    const fn get_mut(&mut self, key: PlayerAction) -> &mut bool {
+    pub const fn get_mut(&mut self, key: PlayerAction) -> &mut bool {
         match key {
             Up => &mut self.up,
             Down => &mut self.down,
             Left => &mut self.left,
             Right => &mut self.right,
             Bleat => &mut self.bleat,
+            Jump => &mut self.jump,
+            Assist => &mut self.assist,
+            Boost => &mut self.boost,
+        }
+    }
+}
+
+/// Actions pressed since the last fixed tick. Input is recorded every frame but played every
+/// tick, so a tap that's released before the next tick stays down until that tick has seen it.
+#[derive(Resource, Debug, Default)]
+struct Latched {
+    pressed: Vec<PlayerAction>,
+    /// Released, but not seen yet
+    released: Vec<PlayerAction>,
+}
+
+impl Latched {
+    fn press(&mut self, key: PlayerAction, input: &mut PlayerInput) {
+        *input.get_mut(key) = true;
+        self.released.retain(|released| *released != key);
+        if !self.pressed.contains(&key) {
+            self.pressed.push(key);
+        }
+    }
+
+    fn release(&mut self, key: PlayerAction, input: &mut PlayerInput) {
+        if !self.pressed.contains(&key) {
+            *input.get_mut(key) = false;
+        } else if !self.released.contains(&key) {
+            self.released.push(key);
         }
     }
 }
 
+/// Runs after every fixed tick, once `FixedUpdate` and the recorder have seen the presses
+fn consume_presses(mut latched: ResMut<Latched>, mut input: ResMut<PlayerInput>) {
+    let Latched { pressed, released } = &mut *latched;
+    for key in released.drain(..) {
+        *input.get_mut(key) = false;
+    }
+    pressed.clear();
+}
+
 fn record_input(
     mut input: MessageReader<KeyboardInput>,
+    keyboard: Res<ButtonInput<KeyCode>>,
     scheme: Res<ControlScheme>,
+    mut latched: ResMut<Latched>,
     mut output: ResMut<PlayerInput>,
 ) {
     for message in input.read() {
         for key in scheme.by_keycode(message.key_code) {
             match message.state {
-                ButtonState::Pressed => *output.get_mut(key) = true,
-                ButtonState::Released => *output.get_mut(key) = false,
+                ButtonState::Pressed => latched.press(key, &mut output),
+                // The action's other key might still be down
+                ButtonState::Released => {
+                    if !scheme
+                        .get(key)
+                        .keys()
+                        .any(|bound| keyboard.pressed(bound.code))
+                    {
+                        latched.release(key, &mut output);
+                    }
+                }
             }
         }
     }
 }
 
+fn record_gamepad(
+    mut input: MessageReader<GamepadButtonStateChangedEvent>,
+    gamepads: Query<&Gamepad>,
+    scheme: Res<ControlScheme>,
+    mut latched: ResMut<Latched>,
+    mut output: ResMut<PlayerInput>,
+) {
+    for message in input.read() {
+        for key in scheme.by_button(message.button) {
+            match message.state {
+                ButtonState::Pressed => latched.press(key, &mut output),
+                ButtonState::Released => latched.release(key, &mut output),
+            }
+        }
+    }
+
+    output.stick = gamepads
+        .iter()
+        .map(|gamepad| past_deadzone(gamepad.left_stick(), scheme.deadzone))
+        .find(|stick| *stick != Vec2::ZERO)
+        .unwrap_or_default();
+}
+
+/// Rescaled so that it still goes smoothly from 0 to 1 past the deadzone
+fn past_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= deadzone {
+        return Vec2::ZERO;
+    }
+    let scaled = ((length - deadzone) / (1. - deadzone)).min(1.);
+    stick / length * scaled
+}
+
+#[must_use]
 pub fn just_pressed(key: PlayerAction) -> impl SystemCondition<()> {
     IntoSystem::into_system(move |input: Res<PlayerInput>, mut pressed: Local<bool>| {
         let new = input.get(key);
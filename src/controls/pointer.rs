@@ -0,0 +1,267 @@
+//! Play with a mouse or a touch screen.
+//!
+//! Holding the pointer down steers the player's sheep towards it, and tapping the sheep makes it
+//! bleat. Touch screens also get a virtual joystick and bleat button, see [`PointerControls`].
+
+use bevy::{input::touch::Touch, prelude::*, window::PrimaryWindow};
+use bevy_modern_pixel_camera::zoom::PixelZoom;
+
+use crate::{
+    AppSystems, PausableSystems,
+    controls::{Latched, PlayerInput, record_gamepad},
+    demo::{level::Level, movement::HumanMind},
+    menus::PlayerAction::Bleat,
+    replay::Replay,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PointerControls>();
+    app.add_systems(
+        Update,
+        (
+            steer,
+            spawn_touch_ui
+                .run_if(show_touch_ui)
+                .run_if(in_state(Screen::Gameplay).or(in_state(Screen::Intro))),
+            joystick.run_if(any_with_component::<TouchUi>),
+        )
+            .chain()
+            // They take over the stick while they're used
+            .after(record_gamepad)
+            .run_if(not(resource_exists::<Replay>))
+            .in_set(AppSystems::RecordInput)
+            .in_set(PausableSystems),
+    );
+}
+
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct PointerControls {
+    pub enabled: bool,
+    /// Show the virtual joystick and bleat button once the screen is touched
+    pub virtual_joystick: bool,
+    /// The sheep slows down when the pointer is closer than this, in pixels
+    pub slow_radius: f32,
+}
+
+impl Default for PointerControls {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            virtual_joystick: true,
+            slow_radius: 40.,
+        }
+    }
+}
+
+/// Tapping this close to the player's sheep bleats instead of steering
+const TAP_RADIUS: f32 = 16.;
+/// In UI pixels
+const JOYSTICK_RADIUS: f32 = 60.;
+const KNOB_RADIUS: f32 = 24.;
+const BLEAT_RADIUS: f32 = 40.;
+const MARGIN: f32 = 30.;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Held {
+    #[default]
+    No,
+    Steering,
+    Bleating,
+}
+
+/// The on-screen controls, only there on touch screens
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct TouchUi;
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct Knob;
+
+/// Where the virtual controls are on the window
+struct Layout {
+    joystick: Vec2,
+    bleat: Vec2,
+}
+
+impl Layout {
+    fn new(window: &Window) -> Self {
+        let y = window.height() - MARGIN - JOYSTICK_RADIUS;
+        Self {
+            joystick: Vec2::new(MARGIN + JOYSTICK_RADIUS, y),
+            bleat: Vec2::new(window.width() - MARGIN - JOYSTICK_RADIUS, y),
+        }
+    }
+
+    fn on_joystick(&self, pos: Vec2) -> bool {
+        pos.distance(self.joystick) <= JOYSTICK_RADIUS
+    }
+
+    fn on_bleat(&self, pos: Vec2) -> bool {
+        pos.distance(self.bleat) <= BLEAT_RADIUS
+    }
+}
+
+fn steer(
+    settings: Res<PointerControls>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<PixelZoom>>,
+    player: Query<&Transform, With<HumanMind>>,
+    touch_ui: Query<(), With<TouchUi>>,
+    mut latched: ResMut<Latched>,
+    mut input: ResMut<PlayerInput>,
+    mut held: Local<Held>,
+) {
+    if !settings.enabled {
+        return;
+    }
+    let layout = (!touch_ui.is_empty()).then(|| Layout::new(&window));
+    // Touches on the virtual controls are handled in `joystick`
+    let touch = touches.iter().find(|touch| {
+        layout.as_ref().is_none_or(|layout| {
+            !layout.on_joystick(touch.start_position()) && !layout.on_bleat(touch.start_position())
+        })
+    });
+    let pointer = if mouse.pressed(MouseButton::Left) {
+        window.cursor_position()
+    } else {
+        touch.map(Touch::position)
+    };
+
+    let Some(pointer) = pointer else {
+        if *held == Held::Bleating {
+            latched.release(Bleat, &mut input);
+        }
+        *held = Held::No;
+        return;
+    };
+    let (camera, camera_transf) = *camera;
+    let (Ok(target), Some(player)) = (
+        camera.viewport_to_world_2d(camera_transf, pointer),
+        player.iter().next(),
+    ) else {
+        return;
+    };
+    let offset = target - player.translation.xy();
+
+    if *held == Held::No {
+        *held = if offset.length() <= TAP_RADIUS {
+            latched.press(Bleat, &mut input);
+            Held::Bleating
+        } else {
+            Held::Steering
+        };
+    }
+    if *held == Held::Steering {
+        input.stick = (offset / settings.slow_radius).clamp_length_max(1.);
+    }
+}
+
+fn show_touch_ui(
+    settings: Res<PointerControls>,
+    touches: Res<Touches>,
+    touch_ui: Query<(), With<TouchUi>>,
+) -> bool {
+    settings.enabled
+        && settings.virtual_joystick
+        && touch_ui.is_empty()
+        && touches.iter().next().is_some()
+}
+
+fn spawn_touch_ui(mut commands: Commands, level: Single<Entity, With<Level>>) {
+    let circle = |radius: f32| Node {
+        position_type: PositionType::Absolute,
+        width: px(2. * radius),
+        height: px(2. * radius),
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        border_radius: BorderRadius::MAX,
+        ..default()
+    };
+    commands.spawn((
+        Name::new("Touch controls"),
+        TouchUi,
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            ..default()
+        },
+        Pickable::IGNORE,
+        ChildOf(*level),
+        children![
+            (
+                Name::new("Joystick"),
+                Node {
+                    left: px(MARGIN),
+                    bottom: px(MARGIN),
+                    ..circle(JOYSTICK_RADIUS)
+                },
+                BackgroundColor(Color::WHITE.with_alpha(0.15)),
+                children![(
+                    Knob,
+                    Node {
+                        left: px(JOYSTICK_RADIUS - KNOB_RADIUS),
+                        top: px(JOYSTICK_RADIUS - KNOB_RADIUS),
+                        ..circle(KNOB_RADIUS)
+                    },
+                    BackgroundColor(Color::WHITE.with_alpha(0.4)),
+                )],
+            ),
+            (
+                Name::new("Bleat button"),
+                Node {
+                    right: px(MARGIN + JOYSTICK_RADIUS - BLEAT_RADIUS),
+                    bottom: px(MARGIN + JOYSTICK_RADIUS - BLEAT_RADIUS),
+                    ..circle(BLEAT_RADIUS)
+                },
+                BackgroundColor(Color::WHITE.with_alpha(0.25)),
+                children![(Text::new("Bleat"), TextFont::from_font_size(20.))],
+            ),
+        ],
+    ));
+}
+
+fn joystick(
+    touches: Res<Touches>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    knob: Query<&mut Node, With<Knob>>,
+    mut latched: ResMut<Latched>,
+    mut input: ResMut<PlayerInput>,
+    mut bleating: Local<bool>,
+) {
+    let layout = Layout::new(&window);
+
+    let stick = touches
+        .iter()
+        .find(|touch| layout.on_joystick(touch.start_position()))
+        .map(|touch| {
+            let offset = (touch.position() - layout.joystick) / JOYSTICK_RADIUS;
+            // The window's y goes down
+            Vec2::new(offset.x, -offset.y).clamp_length_max(1.)
+        });
+    if let Some(stick) = stick {
+        input.stick = stick;
+    }
+    let knob_offset = stick.unwrap_or_default() * (JOYSTICK_RADIUS - KNOB_RADIUS);
+    for mut node in knob {
+        node.left = px(JOYSTICK_RADIUS - KNOB_RADIUS + knob_offset.x);
+        node.top = px(JOYSTICK_RADIUS - KNOB_RADIUS - knob_offset.y);
+    }
+
+    let bleat = touches
+        .iter()
+        .any(|touch| layout.on_bleat(touch.start_position()));
+    if bleat != *bleating {
+        if bleat {
+            latched.press(Bleat, &mut input);
+        } else {
+            latched.release(Bleat, &mut input);
+        }
+        *bleating = bleat;
+    }
+}
@@ -59,31 +59,36 @@ impl Movement for HumanMind {
 impl Movement for SheepMind {
     fn dx(&self) -> f32 {
         match self.state {
-            sheep::State::Moving { goal, .. } => goal.x,
+            sheep::State::Moving { goal, .. } | sheep::State::Fleeing { goal, .. } => goal.x,
             sheep::State::Obseerving { .. } | sheep::State::Idle => 0.,
         }
     }
 
     fn moving(&self) -> bool {
         match self.state {
-            sheep::State::Moving { .. } => true,
+            sheep::State::Moving { .. } | sheep::State::Fleeing { .. } => true,
             sheep::State::Obseerving { .. } | sheep::State::Idle => false,
         }
     }
 }
 
-/// Update the sprite direction and animation state (idling/walking).
+/// Update the sprite direction and animation state (idling/walking/fleeing).
+/// The player looks scared whenever a sheep in its place would be.
 fn update_animation_movement<T: Movement + Component>(
-    mut player_query: Query<(&T, &mut Sprite, &mut SheepAnimation)>,
+    mut player_query: Query<(&T, Option<&SheepMind>, &mut Sprite, &mut SheepAnimation)>,
 ) {
-    for (controller, mut sprite, mut animation) in &mut player_query {
+    for (controller, mind, mut sprite, mut animation) in &mut player_query {
         let dx = controller.dx();
         if dx != 0.0 {
             sprite.flip_x = dx < 0.0;
         }
 
         let animation_state = if controller.moving() {
-            PlayerAnimationState::Walking
+            if mind.is_some_and(SheepMind::fleeing) {
+                PlayerAnimationState::Fleeing
+            } else {
+                PlayerAnimationState::Walking
+            }
         } else {
             PlayerAnimationState::Idling
         };
@@ -136,6 +141,7 @@ pub struct SheepAnimation {
 pub enum PlayerAnimationState {
     Idling,
     Walking,
+    Fleeing,
 }
 
 impl SheepAnimation {
@@ -147,6 +153,8 @@ impl SheepAnimation {
     const WALKING_FRAMES: usize = 5;
     /// The duration of each walking frame.
     const WALKING_INTERVAL: Duration = Duration::from_millis(50);
+    /// Fleeing uses the walking frames, faster.
+    const FLEEING_INTERVAL: Duration = Duration::from_millis(30);
 
     fn idling() -> Self {
         Self {
@@ -164,6 +172,14 @@ impl SheepAnimation {
         }
     }
 
+    fn fleeing() -> Self {
+        Self {
+            timer: Timer::new(Self::FLEEING_INTERVAL, TimerMode::Repeating),
+            frame: 0,
+            state: PlayerAnimationState::Fleeing,
+        }
+    }
+
     pub fn new() -> Self {
         Self::idling()
     }
@@ -177,7 +193,9 @@ impl SheepAnimation {
         self.frame = (self.frame + 1)
             % match self.state {
                 PlayerAnimationState::Idling => Self::IDLE_FRAMES,
-                PlayerAnimationState::Walking => Self::WALKING_FRAMES,
+                PlayerAnimationState::Walking | PlayerAnimationState::Fleeing => {
+                    Self::WALKING_FRAMES
+                }
             };
     }
 
@@ -187,6 +205,7 @@ impl SheepAnimation {
             match state {
                 PlayerAnimationState::Idling => *self = Self::idling(),
                 PlayerAnimationState::Walking => *self = Self::_walking(),
+                PlayerAnimationState::Fleeing => *self = Self::fleeing(),
             }
         }
     }
@@ -200,7 +219,9 @@ impl SheepAnimation {
     pub const fn get_atlas_index(&self) -> usize {
         match self.state {
             PlayerAnimationState::Idling => self.frame,
-            PlayerAnimationState::Walking => Self::IDLE_FRAMES + self.frame,
+            PlayerAnimationState::Walking | PlayerAnimationState::Fleeing => {
+                Self::IDLE_FRAMES + self.frame
+            }
         }
     }
 }
@@ -2,19 +2,23 @@ use bevy::{
     image::{ImageLoaderSettings, ImageSampler},
     prelude::*,
 };
-use rand::{Rng, rng};
+use rand::Rng;
 
 use crate::{
     AppSystems, PausableSystems,
     asset_tracking::LoadResource,
-    audio::sound_effect,
+    audio::{mixer::SoundGroup, sound_effect},
     camera::{GAME_HEIGHT, GAME_WIDTH},
     demo::{
         level::Level,
         movement::HumanMind,
-        sheep::{Sheep, ego::ParticleSpawner},
+        sheep::{
+            Sheep,
+            ego::{CHARGE_PER_CABBAGE, JumpMeter, ParticleSpawner},
+        },
     },
     intro::{CabbageEnabled, IntroPause, Resume},
+    rng::GameRng,
     screens::Screen,
     theme::palette::{LIGHT_GREEN, WHITE},
 };
@@ -22,22 +26,30 @@ use crate::{
 pub(super) fn plugin(app: &mut App) {
     app.load_resource::<CabbageAssets>();
     app.add_systems(
-        Update,
-        (spawn, eat, update_score.run_if(resource_changed::<Score>))
+        FixedUpdate,
+        (spawn, eat)
             .run_if(in_state(Screen::Gameplay))
             .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
     app.add_systems(
-        Update,
-        (spawn, eat, update_score.run_if(resource_changed::<Score>))
+        FixedUpdate,
+        (spawn, eat)
             .run_if(in_state(Screen::Intro))
             .run_if(resource_exists::<CabbageEnabled>)
             .in_set(AppSystems::Update)
             .in_set(PausableSystems),
     );
+    app.add_systems(
+        Update,
+        update_score
+            .run_if(resource_changed::<Score>)
+            .run_if(in_state(Screen::Gameplay).or(in_state(Screen::Intro)))
+            .in_set(AppSystems::Update),
+    );
 
-    app.add_systems(OnEnter(Screen::Gameplay), reset_score);
+    app.init_resource::<CabbageTimer>();
+    app.add_systems(OnEnter(Screen::Gameplay), (reset_score, reset_timer));
 
     app.insert_resource(Score(0));
 }
@@ -75,6 +87,7 @@ pub struct CabbageAssets {
 #[reflect(Component)]
 pub struct Cabbage;
 
+#[derive(Resource, Debug)]
 pub struct CabbageTimer(Timer);
 
 const SECONDS_TO_CABBAGE: f32 = 1.;
@@ -92,11 +105,12 @@ impl Default for CabbageTimer {
 
 fn spawn(
     mut commands: Commands,
-    mut timer: Local<CabbageTimer>,
+    mut timer: ResMut<CabbageTimer>,
     time: Res<Time>,
     level: Query<Entity, With<Level>>,
     cabbages: Query<(), With<Cabbage>>,
     assets: If<Res<CabbageAssets>>,
+    mut rng: ResMut<GameRng>,
 ) {
     let Some(level) = level.iter().next() else {
         return;
@@ -114,8 +128,6 @@ fn spawn(
         return;
     }
 
-    let mut rng = rng();
-
     if rng.random::<f32>() > SPAWN_CHANCE {
         return;
     }
@@ -145,6 +157,7 @@ fn eat(
     cabbages: Query<(Entity, &Transform), With<Cabbage>>,
     sheep: Query<(&Transform, Option<&HumanMind>), With<Sheep>>,
     mut score: ResMut<Score>,
+    mut meter: ResMut<JumpMeter>,
     mut writer: MessageWriter<Resume>,
     pause: Res<IntroPause>,
     assets: Res<CabbageAssets>,
@@ -165,8 +178,9 @@ fn eat(
                     info!("Managed to eat");
                     writer.write(Resume(IntroPause::WaitEat));
                 }
-                commands.spawn(sound_effect(assets.bite.clone(), 0.5));
+                commands.spawn((sound_effect(assets.bite.clone(), 0.5), SoundGroup::Bite));
                 score.0 += 1;
+                meter.fill(CHARGE_PER_CABBAGE);
             }
 
             commands.spawn((
@@ -212,3 +226,7 @@ fn update_score(score: Res<Score>, ui: Query<&mut TextSpan, With<ScoreUI>>) {
 fn reset_score(mut score: ResMut<Score>) {
     score.0 = 0;
 }
+
+fn reset_timer(mut timer: ResMut<CabbageTimer>) {
+    *timer = CabbageTimer::default();
+}
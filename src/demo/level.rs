@@ -4,8 +4,13 @@ use bevy::prelude::*;
 
 use crate::{
     asset_tracking::LoadResource,
-    audio::music,
-    demo::{cabbage::spawn_score, player::PlayerAssets, sheep::new_sheep},
+    audio::adaptive,
+    demo::{
+        cabbage::spawn_score,
+        player::{self, PlayerAssets},
+        sheep::{ego::spawn_meter, new_sheep},
+    },
+    rng::GameRng,
     screens::Screen,
     theme::palette::RESURRECT_PALETTE,
 };
@@ -15,6 +20,11 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(OnEnter(Screen::Gameplay), background);
     app.add_systems(OnEnter(Screen::Intro), background);
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_level);
+    app.add_systems(OnEnter(Screen::Intro), spawn_level);
+    app.add_systems(OnEnter(Screen::Gameplay), player::choose.after(spawn_level));
+    app.add_systems(OnEnter(Screen::Intro), player::choose.after(spawn_level));
 }
 
 #[derive(Resource, Asset, Clone, Reflect)]
@@ -22,6 +32,11 @@ pub(super) fn plugin(app: &mut App) {
 pub struct LevelAssets {
     #[dependency]
     pub music: Handle<AudioSource>,
+    /// Stingers borrow the game's sound effects until they get their own
+    #[dependency]
+    pub jump_stinger: Handle<AudioSource>,
+    #[dependency]
+    pub near_miss_stinger: Handle<AudioSource>,
 }
 
 impl FromWorld for LevelAssets {
@@ -29,6 +44,8 @@ impl FromWorld for LevelAssets {
         let assets = world.resource::<AssetServer>();
         Self {
             music: assets.load("audio/music/sheep.wav"),
+            jump_stinger: assets.load("audio/sound_effects/button_hover.ogg"),
+            near_miss_stinger: assets.load("audio/sound_effects/bite.ogg"),
         }
     }
 }
@@ -45,12 +62,13 @@ pub fn spawn_level(
     state: Res<State<Screen>>,
     player_assets: Res<PlayerAssets>,
     level: Query<(), With<Level>>,
+    mut rng: ResMut<GameRng>,
 ) {
     if level.count() > 0 {
         info!("Level is already spawned");
         return;
     }
-    spawn_level_function(commands, level_assets, player_assets, **state);
+    spawn_level_function(commands, level_assets, player_assets, **state, &mut rng);
 }
 
 /// A system that spawns the main level.
@@ -59,6 +77,7 @@ pub fn spawn_level_function(
     level_assets: Res<LevelAssets>,
     player_assets: Res<PlayerAssets>,
     state: Screen,
+    rng: &mut GameRng,
 ) {
     let level = commands
         .spawn((
@@ -78,7 +97,7 @@ pub fn spawn_level_function(
             DespawnOnExit(Screen::Gameplay),
             children![(
                 Name::new("Gameplay Music"),
-                music(level_assets.music.clone())
+                adaptive::layers(&level_assets.music)
             )],
         ))
         .id();
@@ -92,10 +111,13 @@ pub fn spawn_level_function(
         }
     };
     for _ in 0..max_sheep {
-        commands.spawn((new_sheep(&player_assets, state), ChildOf(level)));
+        commands.spawn((new_sheep(&player_assets, state, rng), ChildOf(level)));
     }
 
     spawn_score(&mut commands, level);
+    if state == Screen::Gameplay {
+        spawn_meter(&mut commands, level);
+    }
 }
 
 pub const BG_COLOR: Color = RESURRECT_PALETTE[35];
@@ -1,4 +1,6 @@
-//! Demo gameplay. All of these modules are only intended for demonstration
+//! Demo gameplay.
+//!
+//! All of these modules are only intended for demonstration
 //! purposes and should be replaced with your own game logic.
 //! Feel free to change the logic found here if you feel like tinkering around
 //! to get a feeling for the template.
@@ -8,7 +10,7 @@ use bevy::prelude::*;
 mod animation;
 pub mod cabbage;
 pub mod level;
-mod movement;
+pub mod movement;
 pub mod player;
 pub mod sheep;
 pub mod wolf;
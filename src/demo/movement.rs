@@ -7,7 +7,7 @@ use crate::{
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
-        Update,
+        FixedUpdate,
         (apply_movement, apply_screen_wrap)
             .chain()
             .in_set(AppSystems::Update)
@@ -27,23 +27,56 @@ pub struct HumanMind {
     /// Maximum speed in world units per second.
     /// 1 world unit = 1 pixel when using the default 2D camera and no physics engine.
     pub max_speed: f32,
+
+    /// Current velocity, chasing `max_speed * intent`.
+    pub velocity: Vec2,
+
+    /// How quickly the character gets going, in world units per second squared.
+    pub acceleration: f32,
+
+    /// How quickly the character stops once there's no intent, same units.
+    pub friction: f32,
 }
 
-const PLAYER_MAX_SPEED: f32 = 200.;
+pub const PLAYER_MAX_SPEED: f32 = 200.;
+/// A sheep's step peaks halfway through its half second, see `sheep::speed_from_time`
+const PLAYER_ACCELERATION: f32 = 4. * PLAYER_MAX_SPEED / 0.5;
+/// Without it, the ease would never get going from a standstill or from top speed
+const MIN_EASE: f32 = 0.15;
 
 impl Default for HumanMind {
     fn default() -> Self {
         Self {
             intent: Vec2::ZERO,
             max_speed: PLAYER_MAX_SPEED,
+            velocity: Vec2::ZERO,
+            acceleration: PLAYER_ACCELERATION,
+            friction: PLAYER_ACCELERATION,
         }
     }
 }
 
-fn apply_movement(time: Res<Time>, mut movement_query: Query<(&HumanMind, &mut Transform)>) {
-    for (controller, mut transform) in &mut movement_query {
-        let velocity = controller.max_speed * controller.intent;
-        transform.translation += velocity.extend(0.0) * time.delta_secs();
+impl HumanMind {
+    /// Speeds up fast at first and gently near the top, and slows down gently at first and
+    /// fast near a standstill. That's the shape of `4t(1 - t)`, the way sheep walk.
+    fn step(&mut self, dt: f32) {
+        let target = self.max_speed * self.intent;
+        let speed = self.velocity.length();
+        let rate = if target.length() >= speed {
+            let ease = (1. - speed / self.max_speed).max(0.).sqrt();
+            self.acceleration * ease.max(MIN_EASE)
+        } else {
+            let ease = (speed / self.max_speed).min(1.);
+            self.friction * (1. - ease).sqrt().max(MIN_EASE)
+        };
+        self.velocity += (target - self.velocity).clamp_length_max(rate * dt);
+    }
+}
+
+fn apply_movement(time: Res<Time>, mut movement_query: Query<(&mut HumanMind, &mut Transform)>) {
+    for (mut controller, mut transform) in &mut movement_query {
+        controller.step(time.delta_secs());
+        transform.translation += controller.velocity.extend(0.0) * time.delta_secs();
     }
 }
 
@@ -51,6 +84,26 @@ fn apply_movement(time: Res<Time>, mut movement_query: Query<(&HumanMind, &mut T
 #[reflect(Component)]
 pub struct ScreenWrap;
 
+/// How hard something is to push around when bumping into others.
+/// Lighter things give way to heavier ones.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct Mass(pub f32);
+
+impl Mass {
+    pub const SHEEP: Self = Self(1.);
+    /// Pushes through the flock
+    pub const PLAYER: Self = Self(20.);
+    /// Only a crowd of sheep can hold one back
+    pub const WOLF: Self = Self(4.);
+}
+
+impl Default for Mass {
+    fn default() -> Self {
+        Self::SHEEP
+    }
+}
+
 fn apply_screen_wrap(mut wrap_query: Query<&mut Transform, With<ScreenWrap>>) {
     let size = Vec2::new(GAME_WIDTH, GAME_HEIGHT);
     let half_size = Vec2::new(GAME_WIDTH, GAME_HEIGHT) / 2.;
@@ -60,3 +113,45 @@ fn apply_screen_wrap(mut wrap_query: Query<&mut Transform, With<ScreenWrap>>) {
         transform.translation = wrapped.extend(transform.translation.z);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eases_like_a_sheep() {
+        let dt = 1. / 64.;
+        let mut mind = HumanMind {
+            intent: Vec2::X,
+            ..default()
+        };
+        let mut speeds = vec![0.];
+        for _ in 0..64 {
+            mind.step(dt);
+            speeds.push(mind.velocity.x);
+            if mind.velocity.x >= PLAYER_MAX_SPEED {
+                break;
+            }
+        }
+        // About a quarter second, like the first half of a sheep's step
+        let secs = (speeds.len() - 1) as f32 * dt;
+        assert!((0.2..0.35).contains(&secs), "{secs}");
+        // Quick at first, gentle at the end
+        assert!(speeds[1] - speeds[0] > speeds[speeds.len() - 1] - speeds[speeds.len() - 2]);
+
+        mind.intent = Vec2::ZERO;
+        let mut speeds = vec![mind.velocity.x];
+        for _ in 0..64 {
+            mind.step(dt);
+            speeds.push(mind.velocity.x);
+            if mind.velocity.x <= 0. {
+                break;
+            }
+        }
+        let secs = (speeds.len() - 1) as f32 * dt;
+        assert!((0.2..0.35).contains(&secs), "{secs}");
+        // Gentle at first, quick towards the end
+        let middle = speeds.len() / 2;
+        assert!(speeds[0] - speeds[1] < speeds[middle] - speeds[middle + 1]);
+    }
+}
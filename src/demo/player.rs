@@ -4,24 +4,26 @@ use bevy::{
     image::{ImageLoaderSettings, ImageSampler},
     prelude::*,
 };
-use rand::{Rng, rng};
+use rand::Rng;
 
 use crate::{
     AppSystems, PausableSystems,
     asset_tracking::LoadResource,
     controls::PlayerInput,
     demo::{
-        movement::HumanMind,
+        movement::{HumanMind, Mass},
         sheep::{Sheep, ego::ParticleSpawner},
     },
+    rng::GameRng,
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.load_resource::<PlayerAssets>();
+    app.add_message::<Jumped>();
 
     // Record directional input as movement controls.
     app.add_systems(
-        Update,
+        FixedUpdate,
         record_player_directional_input
             .in_set(AppSystems::RecordInput)
             .in_set(PausableSystems),
@@ -31,32 +33,87 @@ pub(super) fn plugin(app: &mut App) {
 pub fn choose(
     mut commands: Commands,
     sheep: Query<(Entity, &Transform, Option<&HumanMind>), With<Sheep>>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut count = 0;
-    for (id, pos, human) in sheep {
-        commands.entity(id).remove::<HumanMind>();
-        if human.is_some() {
-            commands.spawn((
-                Transform::from_translation(pos.translation),
-                ParticleSpawner::default(),
-            ));
-        }
-        count += 1;
-    }
-
+    let count = sheep.iter().count();
     if count == 0 {
         error!("No sheep");
         return;
     }
 
-    let new_player = rng().random_range(0..count);
+    let new_player = rng.random_range(0..count);
 
     let Some((id, _, _)) = sheep.iter().nth(new_player) else {
         error!("Sheep somehow disappeared?");
         return;
     };
 
-    commands.entity(id).insert(HumanMind::default());
+    switch_to(&mut commands, &sheep, id);
+}
+
+/// Sheep further than this can't be picked with [`choose_toward`]
+const TOWARD_RANGE: f32 = 150.;
+/// How well lined up with the direction a sheep has to be, as a cosine
+const TOWARD_ALIGNMENT: f32 = 0.7;
+
+/// Like [`choose`], but pick the closest sheep roughly in `direction` from the player, if any
+pub fn choose_toward(
+    mut commands: Commands,
+    sheep: Query<(Entity, &Transform, Option<&HumanMind>), With<Sheep>>,
+    rng: ResMut<GameRng>,
+    direction: Vec2,
+) {
+    let player = sheep
+        .iter()
+        .find(|(_, _, human)| human.is_some())
+        .map(|(_, transf, _)| transf.translation.xy());
+    let (Some(player), Some(direction)) = (player, direction.try_normalize()) else {
+        choose(commands, sheep, rng);
+        return;
+    };
+
+    let target = sheep
+        .iter()
+        .filter(|(_, _, human)| human.is_none())
+        .filter_map(|(id, transf, _)| {
+            let offset = transf.translation.xy() - player;
+            let aligned = offset.normalize_or_zero().dot(direction) >= TOWARD_ALIGNMENT;
+            (aligned && offset.length() <= TOWARD_RANGE).then_some((id, offset.length()))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+
+    match target {
+        Some((id, _)) => switch_to(&mut commands, &sheep, id),
+        None => choose(commands, sheep, rng),
+    }
+}
+
+/// The player went from one sheep to another
+#[derive(Message, Debug)]
+pub struct Jumped;
+
+pub fn switch_to(
+    commands: &mut Commands,
+    sheep: &Query<(Entity, &Transform, Option<&HumanMind>), With<Sheep>>,
+    new_player: Entity,
+) {
+    for (id, pos, human) in sheep {
+        if human.is_some() {
+            commands
+                .entity(id)
+                .remove::<HumanMind>()
+                .insert(Mass::SHEEP);
+            commands.spawn((
+                Transform::from_translation(pos.translation),
+                ParticleSpawner::default(),
+            ));
+            commands.write_message(Jumped);
+        }
+    }
+
+    commands
+        .entity(new_player)
+        .insert((HumanMind::default(), Mass::PLAYER));
 }
 
 fn record_player_directional_input(
@@ -79,8 +136,12 @@ fn record_player_directional_input(
     }
 
     // Normalize intent so that diagonal movement is the same speed as horizontal / vertical.
-    // This should be omitted if the input comes from an analog stick instead.
-    let intent = intent.normalize_or_zero();
+    // The stick already has the right length, and half a push means half the speed.
+    let intent = if input.stick == Vec2::ZERO {
+        intent.normalize_or_zero()
+    } else {
+        input.stick
+    };
 
     // Apply movement intent to controllers.
     for mut controller in &mut controller_query {
@@ -1,12 +1,7 @@
-use std::{
-    cmp::{Ordering, min},
-    collections::{HashMap, hash_map::Entry},
-    f32::consts::PI,
-    time::Duration,
-};
+use std::{cmp::Ordering, f32::consts::PI, time::Duration};
 
 use bevy::prelude::*;
-use rand::{Rng, rng};
+use rand::Rng;
 
 use crate::{
     AppSystems, PausableSystems,
@@ -16,30 +11,57 @@ use crate::{
     demo::{
         animation::SheepAnimation,
         level::{Level, N_SHEEP},
-        movement::{HumanMind, ScreenWrap},
+        movement::{HumanMind, Mass, ScreenWrap},
         player::PlayerAssets,
+        sheep::{
+            boids::{Boids, Neighbor, Velocity},
+            grid::SheepGrid,
+        },
+        wolf::Wolf,
     },
+    interpolation::Interpolated,
     intro::BleatEnabled,
     menus::PlayerAction,
+    rng::GameRng,
     screens::Screen,
 };
 
+pub mod assist;
 pub mod bleat;
+pub mod boids;
+pub mod boost;
 pub mod ego;
+pub mod grid;
+pub mod warning;
 
 pub fn plugin(app: &mut App) {
     app.load_resource::<SheepAssets>();
+    app.init_resource::<SheepGrid>();
+    app.init_resource::<Fear>();
+    app.init_resource::<Boids>();
+    app.init_resource::<bleat::BleatWaves>();
+    app.add_message::<bleat::Bleated>();
+    app.init_resource::<bleat::SheepTimer>();
+    app.add_systems(OnEnter(Screen::Gameplay), bleat::reset_timer);
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (
-            (collision, think, walk).chain(),
+            (
+                grid::rebuild,
+                (collision, think, panic, walk).chain(),
+                bleat::spread,
+            )
+                .chain(),
             bleat::tick,
-            bleat::spread,
             bleat::random,
-            bleat::despawn_image,
             bleat::with_b.run_if(just_pressed(PlayerAction::Bleat)),
-            ego::jump,
+            (
+                ego::spend_meter.run_if(just_pressed(PlayerAction::Jump)),
+                ego::jump,
+                warning::start,
+            )
+                .chain(),
             move_from_edge,
             respawn_dead,
         )
@@ -49,23 +71,37 @@ pub fn plugin(app: &mut App) {
     );
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (
-            (collision, think, walk).chain(),
-            ego::jump,
-            // Tick these so we don't have to wait
-            bleat::tick,
             (
-                bleat::spread,
-                bleat::despawn_image,
-                bleat::with_b.run_if(just_pressed(PlayerAction::Bleat)),
+                grid::rebuild,
+                (collision, think, panic, walk).chain(),
+                bleat::spread.run_if(resource_exists::<BleatEnabled>),
             )
+                .chain(),
+            (ego::jump, warning::start).chain(),
+            // Tick these so we don't have to wait
+            bleat::tick,
+            bleat::with_b
+                .run_if(just_pressed(PlayerAction::Bleat))
                 .run_if(resource_exists::<BleatEnabled>),
         )
             .in_set(AppSystems::Update)
             .run_if(in_state(Screen::Intro)),
     );
 
+    app.add_systems(
+        Update,
+        (
+            bleat::despawn_image,
+            // Only where there's something to draw with
+            bleat::draw_waves.run_if(resource_exists::<GizmoConfigStore>),
+        )
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay).or(in_state(Screen::Intro))),
+    );
+
     // app.add_systems(
     //     Update,
     //     log_offscreen
@@ -75,11 +111,12 @@ pub fn plugin(app: &mut App) {
     //         .run_if(in_state(Screen::Gameplay)),
     // );
 
-    app.add_plugins(ego::plugin);
+    app.add_plugins((assist::plugin, boost::plugin, ego::plugin, warning::plugin));
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 #[reflect(Component)]
+#[require(Mass)]
 pub struct Sheep;
 
 #[derive(Component, Reflect, Debug)]
@@ -89,36 +126,128 @@ pub struct SheepMind {
     time_left: Timer,
 }
 
-const SHEEP_AWARENESS: usize = 4;
+impl SheepMind {
+    #[must_use]
+    pub const fn fleeing(&self) -> bool {
+        matches!(self.state, State::Fleeing { .. })
+    }
+
+    /// How fast the sheep means to go at most, which is more than a walk when it's panicking,
+    /// squeezed or heeding a bleat
+    #[must_use]
+    pub const fn intended_speed(&self) -> f32 {
+        match self.state {
+            State::Moving { speed, .. } | State::Fleeing { speed, .. } => speed,
+            State::Obseerving { .. } | State::Idle => 0.,
+        }
+    }
+
+    /// Walk in `direction` for a while, unless running from a wolf
+    fn heed(&mut self, direction: Vec2, speed: f32) {
+        if self.fleeing() {
+            return;
+        }
+        self.state = State::Moving {
+            goal: direction,
+            speed,
+        };
+        self.time_left.reset();
+    }
+}
 
 #[derive(Reflect, Debug)]
 pub enum State {
-    Moving { goal: Vec2, speed: f32 },
-    Obseerving { neighbors: Vec<Vec2> },
+    Moving {
+        goal: Vec2,
+        speed: f32,
+    },
+    Obseerving {
+        neighbors: Vec<Neighbor>,
+    },
     Idle,
+    /// Running from a wolf, or from whatever spooked the sheep nearby
+    Fleeing {
+        goal: Vec2,
+        speed: f32,
+        /// 1 for a sheep that saw the wolf, less for each sheep the panic went through
+        panic: f32,
+        /// Whether the panic was already passed on to the neighbors
+        spread: bool,
+    },
 }
 
+/// How sheep react to wolves
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Fear {
+    /// Sheep this close to a wolf run away from it
+    pub radius: f32,
+    /// Top speed of a fleeing sheep
+    pub speed: f32,
+    /// Sheep this close to a fleeing sheep flee along with it
+    pub spread_radius: f32,
+    /// How much of the panic is passed on to the neighbors
+    pub spread_falloff: f32,
+    /// Panic weaker than this isn't passed on
+    pub min_panic: f32,
+}
+
+impl Default for Fear {
+    fn default() -> Self {
+        Self {
+            radius: 80.,
+            speed: 200.,
+            spread_radius: 40.,
+            spread_falloff: 0.6,
+            min_panic: 0.3,
+        }
+    }
+}
+
+/// How much a fleeing sheep still runs towards the flock
+const FLOCK_PULL: f32 = 0.3;
+
 impl State {
     const fn new_thinking() -> Self {
         Self::Obseerving { neighbors: vec![] }
     }
 
-    fn target_if_thinking(&mut self, goal: Vec2) {
+    fn target_if_thinking(&mut self, neighbor: Neighbor) {
         let Self::Obseerving { neighbors } = self else {
             return;
         };
 
-        neighbors.push(goal);
+        neighbors.push(neighbor);
     }
 
-    fn conclude_from_observation(&mut self) {
+    /// `threats` are the offsets to the wolves within [`Fear::radius`]
+    fn conclude_from_observation(&mut self, threats: &[Vec2], fear: &Fear, boids: &Boids) {
         let Self::Obseerving { neighbors } = self else {
             return;
         };
 
-        let count = min(neighbors.len(), SHEEP_AWARENESS);
+        if !threats.is_empty() {
+            // Closer wolves are scarier
+            let away = threats
+                .iter()
+                .map(|v| -v.normalize_or_zero() * (1.5 - v.length() / fear.radius))
+                .sum::<Vec2>()
+                .normalize_or_zero();
+            let flock = neighbors
+                .iter()
+                .map(|n| n.offset)
+                .sum::<Vec2>()
+                .normalize_or_zero();
+            *self = Self::Fleeing {
+                goal: away + FLOCK_PULL * flock,
+                speed: fear.speed,
+                panic: 1.,
+                spread: false,
+            };
+            return;
+        }
 
-        if count == 0 {
+        if neighbors.is_empty() {
             *self = Self::Moving {
                 goal: Vec2::ZERO,
                 speed: 0.,
@@ -127,43 +256,28 @@ impl State {
         }
 
         neighbors.sort_unstable_by(|a, b| {
-            a.length()
-                .partial_cmp(&b.length())
+            a.offset
+                .length()
+                .partial_cmp(&b.offset.length())
                 .unwrap_or(Ordering::Equal)
         });
 
-        if neighbors.len() > 1 {
-            assert!(neighbors[0].length() <= neighbors[1].length());
-        }
-
-        if neighbors[0].length() < COLLISION_DISTANCE {
-            *self = Self::Moving {
-                goal: -neighbors[0],
-                speed: 150.,
-            };
+        let goal = boids.steer(neighbors, RANGE);
+        // Hurry if squeezed
+        let speed = if neighbors[0].offset.length() < COLLISION_DISTANCE {
+            150.
         } else {
-            let goal = neighbors
-                .iter()
-                .map(|v| {
-                    if v.length() <= AVOID_RANGE {
-                        -v * 0.5
-                    } else {
-                        *v
-                    }
-                })
-                .take(count)
-                .sum::<Vec2>()
-                / (count as f32);
-            *self = Self::Moving { goal, speed: 100. }
-        }
+            100.
+        };
+        *self = Self::Moving { goal, speed };
     }
 }
 
 impl SheepMind {
-    fn new_idle() -> Self {
+    fn new_idle(rng: &mut impl Rng) -> Self {
         Self {
             time_left: Timer::new(
-                Duration::from_millis(rng().random_range(400..600)),
+                Duration::from_millis(rng.random_range(400..600)),
                 TimerMode::Repeating,
             ),
             state: State::Idle,
@@ -172,9 +286,7 @@ impl SheepMind {
 }
 
 /// No transform, no screenwrap
-pub fn new_sheep(player_assets: &PlayerAssets, state: Screen) -> impl Bundle {
-    let mut rng = rng();
-
+pub fn new_sheep(player_assets: &PlayerAssets, state: Screen, rng: &mut impl Rng) -> impl Bundle {
     let angle = 2. * PI * rng.random::<f32>();
     let pos = if matches!(state, Screen::Intro) {
         let distance = GAME_HEIGHT / 8. * (1. - rng.random::<f32>().powi(2));
@@ -186,7 +298,7 @@ pub fn new_sheep(player_assets: &PlayerAssets, state: Screen) -> impl Bundle {
 
     (
         sheep_base(player_assets),
-        SheepMind::new_idle(),
+        SheepMind::new_idle(rng),
         Transform {
             translation: pos.extend(0.),
             rotation: Quat::IDENTITY,
@@ -197,90 +309,149 @@ pub fn new_sheep(player_assets: &PlayerAssets, state: Screen) -> impl Bundle {
 }
 
 const RANGE: f32 = 150.;
-const AVOID_RANGE: f32 = 50.;
 const COLLISION_DISTANCE: f32 = 25.;
 
 fn collision(
-    mut sheep: Query<(Entity, &mut Transform), With<Sheep>>,
-    mut distances: Local<HashMap<Entity, Vec2>>,
+    grid: Res<SheepGrid>,
+    masses: Query<&Mass>,
+    sheep: Query<(Entity, &Mass, &mut Transform), With<Sheep>>,
 ) {
-    distances.clear();
-
-    let mut combinations = sheep.iter_combinations_mut::<2>();
-    while let Some([(id1, trans1), (id2, trans2)]) = combinations.fetch_next() {
-        let vec = (trans1.translation - trans2.translation).xy();
-        let dist = vec.length();
-
-        if dist >= COLLISION_DISTANCE {
+    for (id, mass, mut transf) in sheep {
+        // Get out of the way of whoever pushes the hardest
+        let Some((need_dist, offset)) = grid
+            .neighbors(id, COLLISION_DISTANCE)
+            .filter(|(_, offset)| offset.length() < COLLISION_DISTANCE)
+            .map(|(other, offset)| {
+                let other = masses.get(other).copied().unwrap_or_default();
+                // Equal masses each move all the way, like they used to
+                let share = (2. * other.0 / (mass.0 + other.0)).min(1.);
+                (share * (COLLISION_DISTANCE - offset.length()), offset)
+            })
+            .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal))
+        else {
             continue;
-        }
+        };
+        transf.translation -= (need_dist * offset.normalize_or_zero()).extend(0.);
+    }
+}
 
-        for (id, vec) in [(id1, vec), (id2, -vec)] {
-            match distances.entry(id) {
-                Entry::Occupied(mut entry) => {
-                    if dist < entry.get().length() {
-                        entry.insert(vec);
-                    }
-                }
-                Entry::Vacant(entry) => {
-                    entry.insert(vec);
+fn think(
+    grid: Res<SheepGrid>,
+    fear: Res<Fear>,
+    boids: Res<Boids>,
+    mut sheep: Query<(Entity, &Transform, &mut SheepMind)>,
+    minds: Query<&Velocity, With<SheepMind>>,
+    wolves: Query<&Transform, With<Wolf>>,
+    time: Res<Time>,
+) {
+    for (id, transf, mut mind) in &mut sheep {
+        mind.time_left.tick(time.delta());
+        if mind.time_left.just_finished() {
+            match &mut mind.state {
+                State::Moving { .. } | State::Fleeing { .. } => mind.state = State::Idle,
+                State::Idle => mind.state = State::new_thinking(),
+                State::Obseerving { .. } => {
+                    error!("Sheep should be done thinking");
+                    mind.state = State::Idle;
                 }
             }
         }
-    }
 
-    for (id, mut transf) in sheep {
-        let Some(vec) = distances.get(&id) else {
-            continue;
-        };
-        let need_dist = COLLISION_DISTANCE - vec.length();
-        transf.translation += (need_dist * vec.normalize_or_zero()).extend(0.);
-    }
-}
+        let pos = transf.translation.xy();
+        let threats: Vec<Vec2> = wolves
+            .iter()
+            .map(|wolf| wolf.translation.xy() - pos)
+            .filter(|offset| offset.length() <= fear.radius)
+            .collect();
+
+        // Don't wait to finish whatever it was doing
+        if !threats.is_empty() && !mind.fleeing() {
+            mind.state = State::new_thinking();
+            mind.time_left.reset();
+        }
 
-fn think(mut sheep: Query<(&Transform, &mut SheepMind)>, time: Res<Time>) {
-    for (_, mut mind) in &mut sheep {
-        mind.time_left.tick(time.delta());
-        if !mind.time_left.just_finished() {
+        if !matches!(mind.state, State::Obseerving { .. }) {
             continue;
         }
-        match &mut mind.state {
-            State::Moving { .. } => mind.state = State::Idle,
-            State::Idle => mind.state = State::new_thinking(),
-            State::Obseerving { .. } => {
-                error!("Sheep should be done thinking");
-                mind.state = State::Idle;
+
+        for (other, offset) in grid.neighbors(id, RANGE) {
+            if let Ok(velocity) = minds.get(other) {
+                mind.state.target_if_thinking(Neighbor {
+                    offset,
+                    heading: velocity.heading,
+                });
             }
         }
+        mind.state
+            .conclude_from_observation(&threats, &fear, &boids);
     }
+}
 
-    let mut combinations = sheep.iter_combinations_mut::<2>();
-    while let Some([(trans1, mut mind1), (trans2, mut mind2)]) = combinations.fetch_next() {
-        let vec = (trans1.translation - trans2.translation).xy();
-        let dist = vec.length();
-        if dist > RANGE {
-            continue;
+/// Fleeing sheep spook their neighbors, which run the same way
+fn panic(grid: Res<SheepGrid>, fear: Res<Fear>, mut sheep: Query<(Entity, &mut SheepMind)>) {
+    let mut spreaders = Vec::new();
+    for (id, mut mind) in &mut sheep {
+        if let State::Fleeing {
+            goal,
+            panic,
+            spread: spread @ false,
+            ..
+        } = &mut mind.state
+        {
+            *spread = true;
+            let passed_on = *panic * fear.spread_falloff;
+            if passed_on >= fear.min_panic {
+                spreaders.push((id, *goal, passed_on));
+            }
         }
-
-        mind1.state.target_if_thinking(-vec);
-        mind2.state.target_if_thinking(vec);
     }
 
-    for (_, mut mind) in &mut sheep {
-        mind.state.conclude_from_observation();
+    for (id, goal, panic) in spreaders {
+        for (other, _) in grid.neighbors(id, fear.spread_radius) {
+            let Ok((_, mut mind)) = sheep.get_mut(other) else {
+                continue;
+            };
+            if mind.fleeing() {
+                continue;
+            }
+            mind.state = State::Fleeing {
+                goal,
+                speed: fear.speed,
+                panic,
+                spread: false,
+            };
+            mind.time_left.reset();
+        }
     }
 }
 
-fn walk(sheep: Query<(&mut Transform, &SheepMind), Without<HumanMind>>, time: Res<Time>) {
-    for (mut transf, mind) in sheep {
-        if let State::Moving { goal, speed, .. } = &mind.state {
-            let speed = *speed
-                * speed_from_time(
-                    mind.time_left.elapsed().as_secs_f32()
-                        / mind.time_left.duration().as_secs_f32(),
-                );
-            let goal = speed * goal.normalize_or_zero().extend(0.);
-            transf.translation += time.delta_secs() * goal;
+fn walk(
+    boids: Res<Boids>,
+    sheep: Query<(&mut Transform, &SheepMind, &mut Velocity), Without<HumanMind>>,
+    players: Query<(&HumanMind, &mut Velocity)>,
+    time: Res<Time>,
+) {
+    let max_turn = boids.max_turn_rate * time.delta_secs();
+    for (mut transf, mind, mut velocity) in sheep {
+        let time_fraction =
+            mind.time_left.elapsed().as_secs_f32() / mind.time_left.duration().as_secs_f32();
+        let (goal, speed) = match &mind.state {
+            State::Moving { goal, speed } => (*goal, *speed * speed_from_time(time_fraction)),
+            State::Fleeing { goal, speed, .. } => {
+                (*goal, *speed * flee_speed_from_time(time_fraction))
+            }
+            State::Obseerving { .. } | State::Idle => (Vec2::ZERO, 0.),
+        };
+        velocity.speed = speed;
+        velocity.turn_towards(goal, max_turn);
+        transf.translation += (time.delta_secs() * velocity.get()).extend(0.);
+    }
+
+    // So the flock can follow the player like any other sheep
+    for (human, mut velocity) in players {
+        velocity.speed = human.velocity.length();
+        if let Some(heading) = human.velocity.try_normalize() {
+            velocity.heading = heading;
         }
     }
 }
@@ -289,6 +460,11 @@ fn speed_from_time(time_fraction: f32) -> f32 {
     4. * time_fraction * (1. - time_fraction)
 }
 
+/// Bolt right away, then tire out
+fn flee_speed_from_time(time_fraction: f32) -> f32 {
+    1. - time_fraction * time_fraction
+}
+
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct SheepAssets {
@@ -296,6 +472,8 @@ pub struct SheepAssets {
     pub bleats: Vec<Handle<AudioSource>>,
     #[dependency]
     pub sound: Handle<Image>,
+    #[dependency]
+    pub warning: Handle<AudioSource>,
 }
 
 impl FromWorld for SheepAssets {
@@ -317,6 +495,7 @@ impl FromWorld for SheepAssets {
                 assets.load("audio/sound_effects/bleat12.ogg"),
             ],
             sound: assets.load("images/sound.png"),
+            warning: assets.load("audio/sound_effects/button_hover.ogg"),
         }
     }
 }
@@ -326,6 +505,7 @@ fn respawn_dead(
     level: Query<Entity, With<Level>>,
     query: Query<(), With<Sheep>>,
     player_assets: Res<PlayerAssets>,
+    mut rng: ResMut<GameRng>,
 ) {
     let count = query.count();
 
@@ -338,7 +518,7 @@ fn respawn_dead(
         return;
     };
 
-    commands.spawn(sheep_at_edge(&player_assets, level));
+    commands.spawn(sheep_at_edge(&player_assets, level, &mut *rng));
 }
 
 #[derive(Component, Reflect, Debug)]
@@ -349,8 +529,8 @@ struct SheepAtEdge {
 
 const DIST_FROM_EDGE: f32 = 20.;
 
-fn sheep_at_edge(player_assets: &PlayerAssets, level: Entity) -> impl Bundle {
-    let (pos, speed) = position_at_edge();
+fn sheep_at_edge(player_assets: &PlayerAssets, level: Entity, rng: &mut impl Rng) -> impl Bundle {
+    let (pos, speed) = position_at_edge(rng);
 
     (
         Transform {
@@ -364,9 +544,9 @@ fn sheep_at_edge(player_assets: &PlayerAssets, level: Entity) -> impl Bundle {
 }
 
 /// Spot, speed to go back to the screen
-pub fn position_at_edge() -> (Vec2, Vec2) {
+pub fn position_at_edge(rng: &mut impl Rng) -> (Vec2, Vec2) {
     let total_edge_len: f32 = 2. * (GAME_WIDTH + GAME_HEIGHT);
-    let spawn_point: f32 = total_edge_len * rng().random::<f32>();
+    let spawn_point: f32 = total_edge_len * rng.random::<f32>();
 
     let (pos, speed) = if spawn_point < GAME_WIDTH {
         // Top
@@ -415,14 +595,10 @@ fn sheep_base(player_assets: &PlayerAssets) -> impl Bundle {
     (
         Name::new("Sheep"),
         Sheep,
+        Velocity::default(),
+        Interpolated::default(),
         bleat::RecentBleat {
             time_to_bleat: Timer::from_seconds(0., TimerMode::Once),
-            time_to_spread: {
-                let mut timer = Timer::from_seconds(bleat::TIME_TO_SPREAD_SECS, TimerMode::Once);
-                timer.finish();
-                timer.tick(Duration::new(1, 0));
-                timer
-            },
         },
         Sprite::from_atlas_image(
             player_assets.sheep.clone(),
@@ -441,6 +617,7 @@ fn move_from_edge(
     mut commands: Commands,
     time: Res<Time>,
     sheep: Query<(Entity, &mut Transform, &SheepAtEdge)>,
+    mut rng: ResMut<GameRng>,
 ) {
     for (id, mut transf, edge) in sheep {
         transf.translation += ENTER_SPEED * time.delta_secs() * edge.speed.extend(0.);
@@ -449,7 +626,7 @@ fn move_from_edge(
             commands
                 .entity(id)
                 .remove::<SheepAtEdge>()
-                .insert((SheepMind::new_idle(), ScreenWrap));
+                .insert((SheepMind::new_idle(&mut *rng), ScreenWrap));
         }
     }
 }
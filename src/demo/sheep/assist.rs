@@ -0,0 +1,117 @@
+//! Help the player find which sheep they are.
+//!
+//! Pressing [`PlayerAction::Assist`] tints the player's sheep for a moment, then it needs to
+//! cool down. It can also be left on all the time, or turned off.
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    controls::just_pressed,
+    demo::{movement::HumanMind, sheep::Sheep},
+    menus::PlayerAction,
+    screens::Screen,
+    theme::palette::RESURRECT_PALETTE,
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Assist>();
+    app.add_systems(OnEnter(Screen::Gameplay), reset);
+    app.add_systems(OnEnter(Screen::Intro), reset);
+
+    app.add_systems(
+        FixedUpdate,
+        (tick, request.run_if(just_pressed(PlayerAction::Assist)))
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay).or(in_state(Screen::Intro))),
+    );
+    app.add_systems(
+        Update,
+        highlight
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay).or(in_state(Screen::Intro))),
+    );
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssistMode {
+    /// The player's sheep is always tinted
+    AlwaysOn,
+    /// Tinted for a moment when asked
+    #[default]
+    OnDemand,
+    Off,
+}
+
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Assist {
+    pub mode: AssistMode,
+    /// How long the tint lasts
+    pub show_secs: f32,
+    /// How long until it can be asked for again, counted from the press
+    pub cooldown_secs: f32,
+    showing_left: f32,
+    cooldown_left: f32,
+}
+
+impl Default for Assist {
+    fn default() -> Self {
+        Self {
+            mode: AssistMode::default(),
+            show_secs: 1.5,
+            cooldown_secs: 5.,
+            showing_left: 0.,
+            cooldown_left: 0.,
+        }
+    }
+}
+
+impl Assist {
+    /// Whether the player's sheep should stand out right now
+    #[must_use]
+    pub fn showing(&self) -> bool {
+        match self.mode {
+            AssistMode::AlwaysOn => true,
+            AssistMode::OnDemand => self.showing_left > 0.,
+            AssistMode::Off => false,
+        }
+    }
+}
+
+const HIGHLIGHT: Color = RESURRECT_PALETTE[18];
+
+fn reset(mut assist: ResMut<Assist>) {
+    assist.showing_left = 0.;
+    assist.cooldown_left = 0.;
+}
+
+fn tick(time: Res<Time>, mut assist: ResMut<Assist>) {
+    let dt = time.delta_secs();
+    assist.showing_left = (assist.showing_left - dt).max(0.);
+    assist.cooldown_left = (assist.cooldown_left - dt).max(0.);
+}
+
+fn request(mut assist: ResMut<Assist>) {
+    if assist.mode != AssistMode::OnDemand || assist.cooldown_left > 0. {
+        return;
+    }
+    assist.showing_left = assist.show_secs;
+    assist.cooldown_left = assist.cooldown_secs;
+}
+
+fn highlight(assist: Res<Assist>, sheep: Query<(&mut Sprite, Has<HumanMind>), With<Sheep>>) {
+    for (mut sprite, human) in sheep {
+        let color = if human && assist.showing() {
+            HIGHLIGHT
+        } else {
+            Color::WHITE
+        };
+        // Don't trigger change detection every frame
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
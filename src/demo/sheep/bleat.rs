@@ -1,19 +1,34 @@
+//! Sheep bleat, and bleats spread through the flock.
+//!
+//! Every bleat sends out a [`BleatWave`], a ring that grows until [`RANGE`] and gets fainter on the
+//! way. Sheep bleat back when the ring reaches them, more likely the closer they are, so a bleat
+//! travels through a tight flock and dies out in a sparse one. The rings can be drawn, see
+//! [`BleatWaves`].
+//!
+//! Sheep the ring goes past turn towards the bleat, and wolves hear it from further away, see
+//! [`Bleated`]. So the player's own bleat gathers the flock around them, but draws the wolves in.
+
 use std::time::Duration;
 
-use bevy::prelude::*;
-use rand::{Rng, rng, seq::IndexedRandom};
+use bevy::{audio::Volume, prelude::*};
+use rand::{Rng, seq::IndexedRandom};
 
 use crate::{
-    audio::sound_effect,
-    demo::{movement::HumanMind, sheep::SheepAssets},
+    audio::{SoundEffect, mixer::SoundGroup, sound_effect, stereo::Positional},
+    demo::{
+        movement::HumanMind,
+        sheep::{SheepAssets, SheepMind, grid::SheepGrid},
+    },
     intro::{IntroPause, Resume},
+    rng::GameRng,
+    screens::Screen,
+    theme::palette::{RED, WHITE},
 };
 
 /// Tick bleat timers
 pub fn tick(time: Res<Time>, sheep: Query<&mut RecentBleat>) {
     for mut recent in sheep {
         recent.time_to_bleat.tick(time.delta());
-        recent.time_to_spread.tick(time.delta());
     }
 }
 
@@ -23,16 +38,17 @@ const SHEEP_BLEAT_DELAY_SECS: f32 = 3.;
 /// B for bleat
 pub fn with_b(
     mut commands: Commands,
-    player_sheep: Query<(Entity, &mut RecentBleat), With<HumanMind>>,
+    player_sheep: Query<(Entity, &Transform, &mut RecentBleat), With<HumanMind>>,
     assets: Res<SheepAssets>,
     mut writer: MessageWriter<Resume>,
     pause: Res<IntroPause>,
 ) {
-    for (id, mut recent) in player_sheep {
+    for (id, transf, mut recent) in player_sheep {
         if !recent.time_to_bleat.is_finished() {
             continue;
         }
-        bleat(&mut commands, &assets, id, &mut recent, true);
+        let pos = transf.translation.xy();
+        bleat(&mut commands, &assets, id, pos, &mut recent, true);
         if matches!(*pause, IntroPause::WaitBleat) {
             info!("Managed to bleat");
             writer.write(Resume(IntroPause::WaitBleat));
@@ -40,20 +56,39 @@ pub fn with_b(
     }
 }
 
-pub const TIME_TO_SPREAD_SECS: f32 = 0.5;
+/// The player's sheep also bleats an octave up, this quietly
+const PLAYER_LAYER_VOLUME: f32 = 0.08;
 
+/// Only the player pressing B is `human_triggered`. The player's sheep also bleats on its own
+/// like any other, which shouldn't give it away or call the flock.
 fn bleat(
     commands: &mut Commands,
     assets: &Res<SheepAssets>,
     id: Entity,
+    pos: Vec2,
     recent: &mut RecentBleat,
     human_triggered: bool,
 ) {
     let rng = &mut rand::rng();
     let random_bleat = assets.bleats.choose(rng).unwrap().clone();
-    let sound_id = commands
-        .spawn((sound_effect(random_bleat, 0.3), BleatSound {}))
-        .id();
+    if human_triggered {
+        commands.spawn((
+            AudioPlayer(random_bleat.clone()),
+            PlaybackSettings {
+                volume: Volume::Linear(PLAYER_LAYER_VOLUME),
+                speed: 2.,
+                ..PlaybackSettings::DESPAWN
+            },
+            SoundEffect,
+            Positional(pos),
+        ));
+    }
+    commands.spawn((
+        sound_effect(random_bleat, 0.3),
+        SoundGroup::Bleat,
+        BleatSound,
+        Positional(pos),
+    ));
 
     recent
         .time_to_bleat
@@ -63,12 +98,24 @@ fn bleat(
             SHEEP_BLEAT_DELAY_SECS
         }));
     recent.time_to_bleat.reset();
-    recent.time_to_spread.reset();
+
+    commands.write_message(Bleated {
+        sheep: id,
+        pos,
+        from_player: human_triggered,
+    });
+    commands.spawn((
+        Name::new("Bleat wave"),
+        BleatWave::new(id, pos, human_triggered),
+        DespawnOnExit(Screen::Gameplay),
+    ));
 
     let child_id = commands
         .spawn((
             Name::new("Bleat image"),
-            BleatImage { sound_id },
+            BleatImage {
+                timer: Timer::from_seconds(BLEAT_IMAGE_SECS, TimerMode::Once),
+            },
             Transform::from_translation(Vec3::new(SOUND_VISUAL_DIST, 0., 0.)),
             Sprite::from_image(assets.sound.clone()),
         ))
@@ -78,24 +125,24 @@ fn bleat(
 
 pub fn despawn_image(
     mut commands: Commands,
-    mut removed: RemovedComponents<BleatSound>,
-    bleats: Query<(Entity, &BleatImage)>,
+    time: Res<Time>,
+    bleats: Query<(Entity, &mut BleatImage)>,
 ) {
-    for sound in removed.read() {
-        for (id, img) in bleats {
-            if img.sound_id == sound {
-                commands.entity(id).despawn();
-            }
+    for (id, mut img) in bleats {
+        if img.timer.tick(time.delta()).is_finished() {
+            commands.entity(id).despawn();
         }
     }
 }
 
 pub const SOUND_VISUAL_DIST: f32 = 16.;
+/// About as long as a bleat. Not tied to the sound, which the mixer may cut short.
+const BLEAT_IMAGE_SECS: f32 = 1.;
 
 #[derive(Component, Reflect, Debug)]
 #[reflect(Component)]
 pub struct BleatImage {
-    sound_id: Entity,
+    timer: Timer,
 }
 
 #[derive(Component, Reflect, Debug)]
@@ -106,50 +153,152 @@ pub struct BleatSound;
 #[reflect(Component)]
 pub struct RecentBleat {
     pub time_to_bleat: Timer,
-    pub time_to_spread: Timer,
 }
 
-const RANGE: f32 = 100.;
-const BLEAT_SPREAD_CHANCE: f32 = 0.05;
+/// A sheep bleated, for whoever listens further than the [`BleatWave`] goes
+#[derive(Message, Debug)]
+pub struct Bleated {
+    pub sheep: Entity,
+    pub pos: Vec2,
+    pub from_player: bool,
+}
+
+/// How bleat waves behave, and whether they're drawn
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct BleatWaves {
+    /// How many times a wave comes back from the edge of its range, each time fainter
+    pub echoes: u8,
+    /// Draw the rings
+    pub show: bool,
+}
+
+/// How far a bleat carries
+pub const RANGE: f32 = 100.;
+/// In pixels per second
+const WAVE_SPEED: f32 = 200.;
+/// The chance a sheep right next to a bleat bleats back, it goes down to 0 at [`RANGE`]
+const BLEAT_SPREAD_CHANCE: f32 = 0.15;
+/// How much of its strength an echo keeps
+const ECHO_STRENGTH: f32 = 0.5;
+/// The chance a sheep turns towards another sheep's bleat, right next to it
+const HEED_CHANCE: f32 = 0.3;
+/// How fast sheep walk towards a bleat
+const HEED_SPEED: f32 = 80.;
+/// The player's bleat brings them at a trot, every sheep it reaches
+pub const GATHER_SPEED: f32 = 130.;
 
+/// A bleat's ring spreading out from where it was bleated
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct BleatWave {
+    /// The sheep that bleated, it doesn't hear itself
+    pub source: Entity,
+    pub origin: Vec2,
+    pub radius: f32,
+    /// 1 for a fresh bleat, less for an echo
+    pub strength: f32,
+    /// Bleated by the player's sheep
+    pub from_player: bool,
+    /// Echoes already heard, compared with [`BleatWaves::echoes`]
+    echo: u8,
+}
+
+impl BleatWave {
+    #[must_use]
+    pub const fn new(source: Entity, origin: Vec2, from_player: bool) -> Self {
+        Self {
+            source,
+            origin,
+            radius: 0.,
+            strength: 1.,
+            from_player,
+            echo: 0,
+        }
+    }
+
+    /// How loud the wave is at `dist` from where it started, from 0 to [`Self::strength`]
+    #[must_use]
+    pub fn falloff(&self, dist: f32) -> f32 {
+        self.strength * (1. - dist / RANGE).max(0.)
+    }
+}
+
+/// Grow the waves, and have the sheep they pass bleat back
 pub fn spread(
     mut commands: Commands,
+    time: Res<Time>,
     assets: If<Res<SheepAssets>>,
-    mut sheep: Query<(Entity, &Transform, &mut RecentBleat, Option<&HumanMind>)>,
+    config: Res<BleatWaves>,
+    grid: Res<SheepGrid>,
+    mut rng: ResMut<GameRng>,
+    waves: Query<(Entity, &mut BleatWave)>,
+    mut sheep: Query<(Entity, &Transform, &mut RecentBleat, Option<&mut SheepMind>)>,
 ) {
-    let mut rng = rng();
-
-    let mut combinations = sheep.iter_combinations_mut::<2>();
-    while let Some(
-        [
-            (id1, trans1, mut bleat1, player1),
-            (id2, trans2, mut bleat2, player2),
-        ],
-    ) = combinations.fetch_next()
-    {
-        let vec = (trans1.translation - trans2.translation).xy();
-        let dist = vec.length();
-        if dist > RANGE {
-            continue;
+    for (wave_id, mut wave) in waves {
+        let passed = wave.radius;
+        wave.radius = WAVE_SPEED
+            .mul_add(time.delta_secs(), wave.radius)
+            .min(RANGE);
+
+        // Only the sheep the front went over since the last time
+        for (other, offset) in grid.near(wave.origin, false, wave.radius) {
+            let dist = offset.length();
+            if dist < passed || other == wave.source {
+                continue;
+            }
+            let Ok((id, transf, mut recent, mind)) = sheep.get_mut(other) else {
+                continue;
+            };
+            let falloff = wave.falloff(dist);
+            if let Some(mut mind) = mind {
+                let toward = -offset.normalize_or_zero();
+                if wave.from_player {
+                    mind.heed(toward, GATHER_SPEED);
+                } else if rng.random::<f32>() < HEED_CHANCE * falloff {
+                    mind.heed(toward, HEED_SPEED);
+                }
+            }
+
+            let chance = BLEAT_SPREAD_CHANCE * falloff;
+            if recent.time_to_bleat.is_finished() && rng.random::<f32>() < chance {
+                let pos = transf.translation.xy();
+                bleat(&mut commands, &assets, id, pos, &mut recent, false);
+            }
         }
 
-        if bleat1.time_to_spread.just_finished()
-            && bleat2.time_to_bleat.is_finished()
-            && rng.random::<f32>() < BLEAT_SPREAD_CHANCE
-        {
-            bleat(&mut commands, &assets, id2, &mut bleat2, player2.is_some());
-        } else if bleat2.time_to_spread.just_finished()
-            && bleat1.time_to_bleat.is_finished()
-            && rng.random::<f32>() < BLEAT_SPREAD_CHANCE
-        {
-            bleat(&mut commands, &assets, id1, &mut bleat1, player1.is_some());
+        if wave.radius < RANGE {
+            continue;
+        }
+        if wave.echo < config.echoes {
+            wave.radius = 0.;
+            wave.strength *= ECHO_STRENGTH;
+            wave.echo += 1;
+        } else {
+            commands.entity(wave_id).despawn();
         }
     }
 }
 
+pub fn draw_waves(config: Res<BleatWaves>, waves: Query<&BleatWave>, mut gizmos: Gizmos) {
+    if !config.show {
+        return;
+    }
+    for wave in waves {
+        // The player's own bleats stand out, to find them in the flock
+        let color = if wave.from_player { RED } else { WHITE };
+        gizmos.circle_2d(
+            wave.origin,
+            wave.radius,
+            color.with_alpha(wave.falloff(wave.radius)),
+        );
+    }
+}
+
 /// This runs per sheep, per tenth of a second
 const SPONTANEOUS_CHANCE: f32 = 0.0005;
 
+#[derive(Resource, Debug)]
 pub struct SheepTimer(Timer);
 
 impl Default for SheepTimer {
@@ -158,12 +307,17 @@ impl Default for SheepTimer {
     }
 }
 
+pub fn reset_timer(mut timer: ResMut<SheepTimer>) {
+    *timer = SheepTimer::default();
+}
+
 pub fn random(
     mut commands: Commands,
-    mut timer: Local<SheepTimer>,
+    mut timer: ResMut<SheepTimer>,
     time: Res<Time>,
     assets: If<Res<SheepAssets>>,
-    sheep: Query<(Entity, &mut RecentBleat, Option<&HumanMind>)>,
+    mut rng: ResMut<GameRng>,
+    sheep: Query<(Entity, &Transform, &mut RecentBleat)>,
 ) {
     timer.0.tick(time.delta());
 
@@ -171,10 +325,10 @@ pub fn random(
         return;
     }
 
-    let mut rng = rng();
-    for (id, mut recent, player) in sheep {
+    for (id, transf, mut recent) in sheep {
         if recent.time_to_bleat.is_finished() && rng.random::<f32>() < SPONTANEOUS_CHANCE {
-            bleat(&mut commands, &assets, id, &mut recent, player.is_some());
+            let pos = transf.translation.xy();
+            bleat(&mut commands, &assets, id, pos, &mut recent, false);
         }
     }
 }
@@ -0,0 +1,116 @@
+//! How a sheep picks where to go from what its neighbors are doing.
+//! See <https://www.red3d.com/cwr/boids/>.
+
+use bevy::prelude::*;
+
+/// The parts of the flocking model, tweak them in the inspector
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Boids {
+    /// How many of the closest neighbors a sheep pays attention to
+    pub awareness: usize,
+    /// Neighbors closer than this push the sheep away
+    pub separation_range: f32,
+    pub separation: f32,
+    /// Pull towards the heading of the neighbors
+    pub alignment: f32,
+    /// Pull towards the middle of the neighbors
+    pub cohesion: f32,
+    /// In radians per second
+    pub max_turn_rate: f32,
+}
+
+impl Default for Boids {
+    fn default() -> Self {
+        Self {
+            awareness: 6,
+            separation_range: 50.,
+            separation: 1.5,
+            alignment: 1.,
+            cohesion: 1.,
+            max_turn_rate: 6.,
+        }
+    }
+}
+
+/// Another sheep, as seen by the one thinking
+#[derive(Reflect, Debug, Clone, Copy)]
+pub struct Neighbor {
+    pub offset: Vec2,
+    pub heading: Vec2,
+}
+
+impl Boids {
+    /// Where to go, given the neighbors sorted by distance.
+    /// `range` is how far a sheep can see.
+    #[must_use]
+    pub fn steer(&self, neighbors: &[Neighbor], range: f32) -> Vec2 {
+        let neighbors = &neighbors[..neighbors.len().min(self.awareness)];
+        if neighbors.is_empty() {
+            return Vec2::ZERO;
+        }
+        let count = neighbors.len() as f32;
+
+        let separation = neighbors
+            .iter()
+            .filter(|n| n.offset.length() < self.separation_range)
+            .map(|n| {
+                -n.offset.normalize_or_zero() * (1. - n.offset.length() / self.separation_range)
+            })
+            .sum::<Vec2>();
+        let alignment =
+            (neighbors.iter().map(|n| n.heading).sum::<Vec2>() / count).clamp_length_max(1.);
+        // Weaker when the flock is already close
+        let cohesion = neighbors.iter().map(|n| n.offset).sum::<Vec2>() / count / range;
+
+        self.separation * separation + self.alignment * alignment + self.cohesion * cohesion
+    }
+}
+
+/// Where a sheep (or a wolf) is heading and how fast.
+/// The heading is kept while standing, so the flock remembers where it was going.
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct Velocity {
+    /// Unit length, or zero if the sheep never moved
+    pub heading: Vec2,
+    pub speed: f32,
+}
+
+impl Velocity {
+    #[must_use]
+    pub fn get(&self) -> Vec2 {
+        self.speed * self.heading
+    }
+
+    /// Turn towards `goal`, but no more than `max_angle`
+    pub fn turn_towards(&mut self, goal: Vec2, max_angle: f32) {
+        let Some(goal) = goal.try_normalize() else {
+            return;
+        };
+        if self.heading == Vec2::ZERO {
+            self.heading = goal;
+            return;
+        }
+        let angle = self.heading.angle_to(goal).clamp(-max_angle, max_angle);
+        self.heading = Vec2::from_angle(angle).rotate(self.heading).normalize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turning_is_limited() {
+        let mut velocity = Velocity {
+            heading: Vec2::X,
+            speed: 1.,
+        };
+        velocity.turn_towards(-Vec2::Y, 0.1);
+        assert!((velocity.heading.to_angle() + 0.1).abs() < 1e-5);
+
+        velocity.turn_towards(Vec2::new(1., -0.05), 0.1);
+        assert!((velocity.heading - Vec2::new(1., -0.05).normalize()).length() < 1e-5);
+    }
+}
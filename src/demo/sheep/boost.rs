@@ -0,0 +1,191 @@
+//! Let the player sprint for a moment.
+//!
+//! Holding [`PlayerAction::Boost`] speeds the player's sheep up over [`Boost::ramp_secs`] and
+//! drains stamina. Once it runs out it has to recover for a bit before the next boost.
+//! Sprinting away from a calm flock is the easiest way to catch a wolf's eye.
+
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems, PausableSystems,
+    controls::PlayerInput,
+    demo::{
+        movement::{HumanMind, PLAYER_MAX_SPEED},
+        sheep::{boids::Velocity, ego::ParticleSpawner, grid::SheepGrid},
+    },
+    screens::Screen,
+    theme::palette::RESURRECT_PALETTE,
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Boost>();
+    app.add_systems(OnEnter(Screen::Gameplay), reset);
+    app.add_systems(OnEnter(Screen::Intro), reset);
+
+    app.add_systems(
+        FixedUpdate,
+        (boost, stand_out)
+            .chain()
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay).or(in_state(Screen::Intro))),
+    );
+    app.add_systems(
+        Update,
+        dust.in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay).or(in_state(Screen::Intro))),
+    );
+}
+
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Boost {
+    /// From 0 to 1
+    pub stamina: f32,
+    /// Top speed, as a multiple of the usual one
+    pub speed_factor: f32,
+    /// How long it takes to get to top speed
+    pub ramp_secs: f32,
+    /// Stamina used per second of boosting
+    pub drain_per_sec: f32,
+    pub recover_per_sec: f32,
+    /// After running out, no boosting until the stamina is back to this
+    pub min_stamina: f32,
+    /// How much the player sticks out from the sheep around them, from 0 to 1
+    pub stand_out: f32,
+    active: bool,
+    boosting_secs: f32,
+    exhausted: bool,
+}
+
+impl Default for Boost {
+    fn default() -> Self {
+        Self {
+            stamina: 1.,
+            speed_factor: 1.8,
+            ramp_secs: 0.3,
+            drain_per_sec: 0.8,
+            recover_per_sec: 0.25,
+            min_stamina: 0.3,
+            stand_out: 0.,
+            active: false,
+            boosting_secs: 0.,
+            exhausted: false,
+        }
+    }
+}
+
+impl Boost {
+    #[must_use]
+    pub const fn active(&self) -> bool {
+        self.active
+    }
+
+    /// How much faster the player is going right now
+    #[must_use]
+    pub fn factor(&self) -> f32 {
+        let t = (self.boosting_secs / self.ramp_secs).clamp(0., 1.);
+        // Ease in and out
+        let eased = t * t * (3. - 2. * t);
+        1. + (self.speed_factor - 1.) * eased
+    }
+}
+
+fn reset(mut boost: ResMut<Boost>) {
+    boost.stamina = 1.;
+    boost.stand_out = 0.;
+    boost.active = false;
+    boost.boosting_secs = 0.;
+    boost.exhausted = false;
+}
+
+fn boost(
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    mut boost: ResMut<Boost>,
+    players: Query<&mut HumanMind>,
+) {
+    let dt = time.delta_secs();
+    let moving = players.iter().any(|human| human.intent != Vec2::ZERO);
+    boost.active = input.boost && moving && !boost.exhausted;
+
+    if boost.active {
+        boost.boosting_secs = (boost.boosting_secs + dt).min(boost.ramp_secs);
+        boost.stamina -= boost.drain_per_sec * dt;
+        if boost.stamina <= 0. {
+            boost.stamina = 0.;
+            boost.exhausted = true;
+        }
+    } else {
+        // Slow down along the same curve
+        boost.boosting_secs = (boost.boosting_secs - dt).max(0.);
+        boost.stamina = (boost.stamina + boost.recover_per_sec * dt).min(1.);
+        if boost.stamina >= boost.min_stamina {
+            boost.exhausted = false;
+        }
+    }
+
+    let factor = boost.factor();
+    for mut human in players {
+        human.max_speed = PLAYER_MAX_SPEED * factor;
+    }
+}
+
+/// Sheep closer than this count as the player's flock
+const FLOCK_RANGE: f32 = 80.;
+
+fn stand_out(
+    mut boost: ResMut<Boost>,
+    grid: Res<SheepGrid>,
+    players: Query<(Entity, &Velocity), With<HumanMind>>,
+    velocities: Query<&Velocity>,
+) {
+    let Some((id, velocity)) = players.iter().next() else {
+        boost.stand_out = 0.;
+        return;
+    };
+    if !boost.active || velocity.speed <= 0. {
+        boost.stand_out = 0.;
+        return;
+    }
+
+    let (count, total) = grid
+        .neighbors(id, FLOCK_RANGE)
+        .filter_map(|(other, _)| velocities.get(other).ok())
+        .fold((0, 0.), |(count, total), other| {
+            (count + 1, total + other.speed)
+        });
+    // Running alone sticks out the most
+    let flock_speed = if count > 0 { total / count as f32 } else { 0. };
+    boost.stand_out = ((velocity.speed - flock_speed) / velocity.speed).clamp(0., 1.);
+}
+
+const DUST: Color = RESURRECT_PALETTE[4];
+const DUST_INTERVAL_SECS: f32 = 0.05;
+
+fn dust(
+    mut commands: Commands,
+    time: Res<Time>,
+    boost: Res<Boost>,
+    players: Query<&Transform, With<HumanMind>>,
+    mut since_last: Local<f32>,
+) {
+    if !boost.active() {
+        *since_last = 0.;
+        return;
+    }
+    *since_last += time.delta_secs();
+    if *since_last < DUST_INTERVAL_SECS {
+        return;
+    }
+    *since_last = 0.;
+
+    for transf in players {
+        commands.spawn((
+            Name::new("Dust"),
+            Transform::from_translation(transf.translation),
+            ParticleSpawner::new(DUST, 0.5, 1, 0.3),
+        ));
+    }
+}
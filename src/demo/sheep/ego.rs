@@ -6,11 +6,24 @@ use rand::{Rng, rng};
 use crate::{
     AppSystems, PausableSystems,
     demo::{movement::HumanMind, player, sheep::Sheep},
+    rng::GameRng,
     screens::Screen,
-    theme::palette::RED,
+    theme::palette::{RED, WHITE},
 };
 
 pub fn plugin(app: &mut App) {
+    app.init_resource::<JumpMeter>();
+    app.init_resource::<JumpTimer>();
+    app.add_message::<EarlyJump>();
+    app.add_systems(OnEnter(Screen::Gameplay), (reset_meter, reset_timer));
+    app.add_systems(OnEnter(Screen::Intro), reset_timer);
+    app.add_systems(
+        Update,
+        update_meter
+            .run_if(resource_changed::<JumpMeter>)
+            .run_if(in_state(Screen::Gameplay))
+            .in_set(AppSystems::Update),
+    );
     app.add_systems(
         Update,
         (fire, particle)
@@ -27,35 +40,159 @@ pub fn plugin(app: &mut App) {
     );
 }
 
-pub struct JumpTimer(Timer);
+/// Counts down to the next time the player switches sheep
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct JumpTimer {
+    pub timer: Timer,
+    /// Where the player goes next, picked when the [`warning`](super::warning) starts
+    pub next: Option<Entity>,
+}
 
 impl Default for JumpTimer {
     fn default() -> Self {
-        Self(Timer::from_seconds(0., TimerMode::Repeating))
+        Self {
+            timer: Timer::from_seconds(0., TimerMode::Repeating),
+            next: None,
+        }
     }
 }
 
+fn reset_timer(mut timer: ResMut<JumpTimer>) {
+    *timer = JumpTimer::default();
+}
+
 const JUMP_TIME_MIN: f32 = 2.;
 const JUMP_TIME_MAX: f32 = 5.;
 
 pub fn jump(
-    commands: Commands,
+    mut commands: Commands,
     time: Res<Time>,
-    mut timer: Local<JumpTimer>,
+    mut timer: ResMut<JumpTimer>,
     sheep: Query<(Entity, &Transform, Option<&HumanMind>), With<Sheep>>,
+    mut rng: ResMut<GameRng>,
+    mut early: MessageReader<EarlyJump>,
 ) {
-    timer.0.tick(time.delta());
+    timer.timer.tick(time.delta());
+
+    let early = early.read().count() > 0;
+    if !early && !timer.timer.just_finished() {
+        return;
+    }
+
+    timer.timer.set_duration(Duration::from_secs_f32(
+        rng.random_range(JUMP_TIME_MIN..JUMP_TIME_MAX),
+    ));
+    timer.timer.reset();
+    let next = timer.next.take();
+
+    if early {
+        // Go where the player is heading
+        let direction = sheep
+            .iter()
+            .find_map(|(_, _, human)| human.map(|human| human.intent))
+            .unwrap_or_default();
+        player::choose_toward(commands, sheep, rng, direction);
+    } else if let Some(next) = next.filter(|next| sheep.contains(*next)) {
+        player::switch_to(&mut commands, &sheep, next);
+    } else {
+        player::choose(commands, sheep, rng);
+    }
+}
+
+/// Filled by eating cabbage, spent on [`crate::menus::PlayerAction::Jump`] to switch sheep
+/// before the timer runs out
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct JumpMeter {
+    pub charge: f32,
+    /// Whether the player can jump early at all
+    pub enabled: bool,
+}
+
+impl Default for JumpMeter {
+    fn default() -> Self {
+        Self {
+            charge: 0.,
+            enabled: true,
+        }
+    }
+}
 
-    if !timer.0.just_finished() {
+pub const JUMP_COST: f32 = 1.;
+pub const CHARGE_PER_CABBAGE: f32 = JUMP_COST / 3.;
+
+impl JumpMeter {
+    pub fn fill(&mut self, amount: f32) {
+        self.charge = (self.charge + amount).min(JUMP_COST);
+    }
+}
+
+#[derive(Message, Debug)]
+pub struct EarlyJump;
+
+pub fn spend_meter(mut meter: ResMut<JumpMeter>, mut writer: MessageWriter<EarlyJump>) {
+    if !meter.enabled || meter.charge < JUMP_COST {
         return;
     }
+    meter.charge -= JUMP_COST;
+    writer.write(EarlyJump);
+}
+
+fn reset_meter(mut meter: ResMut<JumpMeter>) {
+    meter.charge = 0.;
+}
 
-    timer.0.set_duration(Duration::from_secs_f32(
-        rng().random_range(JUMP_TIME_MIN..JUMP_TIME_MAX),
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct MeterUI;
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct MeterFill;
+
+pub fn spawn_meter(commands: &mut Commands, level: Entity) {
+    commands.spawn((
+        Name::new("Jump meter UI"),
+        MeterUI,
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(44),
+            right: px(12),
+            width: px(80),
+            height: px(10),
+            border: UiRect::all(px(1)),
+            ..Default::default()
+        },
+        BorderColor::all(WHITE),
+        ChildOf(level),
+        children![(
+            MeterFill,
+            Node {
+                width: percent(0),
+                height: percent(100),
+                ..Default::default()
+            },
+            BackgroundColor(RED),
+        )],
     ));
-    timer.0.reset();
+}
 
-    player::choose(commands, sheep);
+fn update_meter(
+    meter: Res<JumpMeter>,
+    ui: Query<&mut Visibility, With<MeterUI>>,
+    fill: Query<&mut Node, With<MeterFill>>,
+) {
+    for mut visibility in ui {
+        *visibility = if meter.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for mut node in fill {
+        node.width = percent(100. * meter.charge / JUMP_COST);
+    }
 }
 
 #[derive(Component, Reflect, Debug)]
@@ -85,6 +222,7 @@ impl ParticleSpawner {
         rng().random_range(2..=6)
     }
 
+    #[must_use]
     pub fn new(color: Color, freq: f32, charges: u8, particle_speed: f32) -> Self {
         Self {
             color,
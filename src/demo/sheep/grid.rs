@@ -0,0 +1,167 @@
+//! Spatial hash over every sheep, rebuilt once per frame, so neighbor queries
+//! only look at nearby cells instead of the whole flock.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    camera::{GAME_HEIGHT, GAME_WIDTH},
+    demo::{movement::ScreenWrap, sheep::Sheep},
+};
+
+/// Has to divide both `GAME_WIDTH` and `GAME_HEIGHT`, so the cells tile the wrapped screen.
+const CELL_SIZE: f32 = 40.;
+const COLUMNS: usize = (GAME_WIDTH / CELL_SIZE) as usize;
+const ROWS: usize = (GAME_HEIGHT / CELL_SIZE) as usize;
+
+#[derive(Resource, Debug, Default)]
+pub struct SheepGrid {
+    entries: Vec<GridEntry>,
+    index: HashMap<Entity, usize>,
+    /// `ROWS * COLUMNS` cells, holding indices into `entries`
+    cells: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GridEntry {
+    id: Entity,
+    pos: Vec2,
+    /// Only two sheep that both wrap around the screen see each other across the edge
+    wrap: bool,
+}
+
+impl SheepGrid {
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+        self.cells.resize_with(ROWS * COLUMNS, Vec::new);
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+    }
+
+    pub fn insert(&mut self, id: Entity, pos: Vec2, wrap: bool) {
+        let (column, row) = cell_of(pos);
+        self.cells[row * COLUMNS + column].push(self.entries.len());
+        self.index.insert(id, self.entries.len());
+        self.entries.push(GridEntry { id, pos, wrap });
+    }
+
+    /// Every other sheep within `radius` of `id`, with the offset from `id` to it.
+    pub fn neighbors(&self, id: Entity, radius: f32) -> impl Iterator<Item = (Entity, Vec2)> {
+        let me = self.index.get(&id).map(|&i| self.entries[i]);
+
+        me.into_iter().flat_map(move |me| self.around(me, radius))
+    }
+
+    /// Every sheep within `radius` of something that isn't in the grid, like a wolf
+    pub fn near(&self, pos: Vec2, wrap: bool, radius: f32) -> impl Iterator<Item = (Entity, Vec2)> {
+        let me = GridEntry {
+            id: Entity::PLACEHOLDER,
+            pos,
+            wrap,
+        };
+        self.around(me, radius)
+    }
+
+    fn around(&self, me: GridEntry, radius: f32) -> impl Iterator<Item = (Entity, Vec2)> {
+        let (column, row) = cell_of(me.pos);
+        let reach = (radius / CELL_SIZE).ceil() as usize;
+        let columns = span(column, reach, COLUMNS);
+
+        span(row, reach, ROWS)
+            .flat_map(move |row| columns.clone().map(move |column| row * COLUMNS + column))
+            .flat_map(|cell| &self.cells[cell])
+            .filter_map(move |&i| {
+                let other = self.entries[i];
+                if other.id == me.id {
+                    return None;
+                }
+                let offset = offset(me, other);
+                (offset.length() <= radius).then_some((other.id, offset))
+            })
+    }
+}
+
+fn cell_of(pos: Vec2) -> (usize, usize) {
+    let column = ((pos.x + GAME_WIDTH / 2.) / CELL_SIZE).floor();
+    let row = ((pos.y + GAME_HEIGHT / 2.) / CELL_SIZE).floor();
+    (
+        column.rem_euclid(COLUMNS as f32) as usize,
+        row.rem_euclid(ROWS as f32) as usize,
+    )
+}
+
+/// Cell indices within `reach` of `center`, wrapping around and without repeats
+fn span(center: usize, reach: usize, len: usize) -> impl Iterator<Item = usize> + Clone {
+    let count = (2 * reach + 1).min(len);
+    let start = (center + len - reach % len) % len;
+    (0..count).map(move |i| (start + i) % len)
+}
+
+fn offset(from: GridEntry, to: GridEntry) -> Vec2 {
+    let offset = to.pos - from.pos;
+    if from.wrap && to.wrap {
+        let size = Vec2::new(GAME_WIDTH, GAME_HEIGHT);
+        (offset + size / 2.).rem_euclid(size) - size / 2.
+    } else {
+        offset
+    }
+}
+
+pub fn rebuild(
+    mut grid: ResMut<SheepGrid>,
+    sheep: Query<(Entity, &Transform, Has<ScreenWrap>), With<Sheep>>,
+) {
+    grid.clear();
+    for (id, transf, wrap) in sheep {
+        grid.insert(id, transf.translation.xy(), wrap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    fn brute_force(grid: &SheepGrid, id: Entity, radius: f32) -> Vec<(Entity, Vec2)> {
+        let me = grid.entries[grid.index[&id]];
+        grid.entries
+            .iter()
+            .filter(|other| other.id != id)
+            .map(|other| (other.id, offset(me, *other)))
+            .filter(|(_, offset)| offset.length() <= radius)
+            .collect()
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut grid = SheepGrid::default();
+        grid.clear();
+
+        for _ in 0..500 {
+            // Sheep walking in from the edge don't wrap, and can be off screen
+            let wrap = rng.random::<f32>() < 0.9;
+            let margin = if wrap { 0. } else { 40. };
+            let pos = Vec2::new(
+                rng.random_range((-GAME_WIDTH / 2. - margin)..(GAME_WIDTH / 2. + margin)),
+                rng.random_range((-GAME_HEIGHT / 2. - margin)..(GAME_HEIGHT / 2. + margin)),
+            );
+            grid.insert(world.spawn_empty().id(), pos, wrap);
+        }
+
+        for radius in [25., 100., 150., 400.] {
+            for entry in &grid.entries {
+                let mut fast: Vec<_> = grid.neighbors(entry.id, radius).collect();
+                let mut slow = brute_force(&grid, entry.id, radius);
+                fast.sort_by_key(|(id, _)| *id);
+                slow.sort_by_key(|(id, _)| *id);
+                assert_eq!(fast, slow, "radius {radius}");
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Warn the player before they switch sheep.
+//!
+//! A ring closes in on the player's sheep during the last [`JumpWarning::lead`] seconds before the
+//! jump, with a soft sound when it appears. The sheep they'll end up in can be marked too.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    AppSystems, PausableSystems,
+    audio::sound_effect,
+    demo::{
+        movement::HumanMind,
+        sheep::{Sheep, SheepAssets, ego::JumpTimer},
+        wolf::DifficultySecs,
+    },
+    rng::GameRng,
+    screens::Screen,
+    theme::palette::{RED, WHITE},
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<JumpWarning>();
+
+    // Only where there's something to draw with
+    app.add_systems(
+        Update,
+        draw.run_if(resource_exists::<GizmoConfigStore>)
+            .run_if(in_state(Screen::Gameplay).or(in_state(Screen::Intro)))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// How far ahead the player hears about a jump
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct JumpWarning {
+    /// At the start of a run, 0 turns the warning off
+    pub lead_secs: f32,
+    /// What the lead time shrinks to as the run gets harder
+    pub hard_lead_secs: f32,
+    /// How long it takes to get there
+    pub hard_after_secs: f32,
+    pub sound: bool,
+    /// Also mark the sheep the player is going to
+    pub show_destination: bool,
+}
+
+impl Default for JumpWarning {
+    fn default() -> Self {
+        Self {
+            lead_secs: 1.,
+            hard_lead_secs: 0.5,
+            hard_after_secs: 120.,
+            sound: true,
+            show_destination: false,
+        }
+    }
+}
+
+impl JumpWarning {
+    /// The lead time after `secs` into the run
+    #[must_use]
+    pub fn lead(&self, secs: f32) -> f32 {
+        let t = (secs / self.hard_after_secs).clamp(0., 1.);
+        // Never longer than what the player asked for
+        self.lead_secs
+            .min(self.lead_secs.lerp(self.hard_lead_secs, t))
+    }
+}
+
+/// Picks where the player goes next once the warning starts
+pub fn start(
+    mut commands: Commands,
+    warning: Res<JumpWarning>,
+    dif: Res<DifficultySecs>,
+    mut timer: ResMut<JumpTimer>,
+    sheep: Query<Entity, With<Sheep>>,
+    assets: If<Res<SheepAssets>>,
+    mut rng: ResMut<GameRng>,
+) {
+    if timer.timer.remaining_secs() > warning.lead(dif.0) {
+        return;
+    }
+    let started = timer.next.is_some();
+    if timer.next.is_some_and(|next| sheep.contains(next)) {
+        return;
+    }
+
+    let count = sheep.iter().count();
+    if count == 0 {
+        return;
+    }
+    timer.next = sheep.iter().nth(rng.random_range(0..count));
+
+    if warning.sound && !started {
+        commands.spawn(sound_effect(assets.warning.clone(), 0.3));
+    }
+}
+
+const RING_START: f32 = 30.;
+const RING_END: f32 = 8.;
+
+fn draw(
+    timer: Res<JumpTimer>,
+    warning: Res<JumpWarning>,
+    dif: Res<DifficultySecs>,
+    player: Query<&Transform, With<HumanMind>>,
+    sheep: Query<&Transform, With<Sheep>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(next) = timer.next else {
+        return;
+    };
+    let lead = warning.lead(dif.0);
+    if lead <= 0. {
+        return;
+    }
+    let left = (timer.timer.remaining_secs() / lead).clamp(0., 1.);
+
+    for transf in player {
+        let radius = RING_END + left * (RING_START - RING_END);
+        gizmos.circle_2d(transf.translation.xy(), radius, RED);
+    }
+
+    if !warning.show_destination {
+        return;
+    }
+    if let Ok(transf) = sheep.get(next) {
+        gizmos.circle_2d(
+            transf.translation.xy(),
+            RING_END,
+            WHITE.with_alpha(1. - left),
+        );
+    }
+}
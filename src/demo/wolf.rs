@@ -1,56 +1,95 @@
-use std::{cmp::Ordering, time::Duration};
+use std::{cmp::Ordering, f32::consts::PI, time::Duration};
 
 use bevy::{
     image::{ImageLoaderSettings, ImageSampler},
     prelude::*,
     sprite_render::Material2dPlugin,
 };
+use rand::Rng;
 
 use crate::{
     AppSystems, PausableSystems,
     asset_tracking::LoadResource,
     demo::{
         level::Level,
-        movement::HumanMind,
-        sheep::{Sheep, position_at_edge},
-        wolf::halo::HaloMaterial,
+        movement::{HumanMind, Mass},
+        sheep::{
+            Sheep, bleat::Bleated, boids::Velocity, boost::Boost, grid::SheepGrid, position_at_edge,
+        },
+        wolf::{
+            detective::{Detective, Suspicion, WolfKinds},
+            halo::HaloMaterial,
+            pack::{Pack, Role},
+        },
     },
+    interpolation::Interpolated,
+    rng::GameRng,
     screens::Screen,
+    theme::palette::RESURRECT_PALETTE,
 };
 
+pub mod detective;
 mod halo;
+pub mod pack;
 
 pub fn plugin(app: &mut App) {
     app.init_asset::<HaloMaterial>();
     app.add_plugins(Material2dPlugin::<HaloMaterial>::default());
     app.load_resource::<WolfAssets>();
     app.init_resource::<DifficultySecs>();
+    app.init_resource::<Pack>();
+    app.init_resource::<WolfKinds>();
+    app.add_message::<NearMiss>();
 
-    app.add_systems(OnEnter(Screen::Gameplay), reset_difficulty);
+    app.init_resource::<WolfSpawnStatus>();
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (reset_difficulty, reset_spawn, pack::reset),
+    );
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (
             spawn,
-            (think_eat, hunt).chain(),
-            rotate_halo,
-            animate_halo,
+            (
+                (detective::add_suspicion, detective::watch)
+                    .chain()
+                    .run_if(any_with_component::<Detective>),
+                think_eat,
+                hear,
+                pack::plan,
+                hunt,
+                push_back,
+            )
+                .chain(),
             update_difficulty,
         )
             .in_set(AppSystems::Update)
             .in_set(PausableSystems)
             .run_if(in_state(Screen::Gameplay)),
     );
+    app.add_systems(
+        Update,
+        (rotate_halo, animate_halo, update_sprite)
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems)
+            .run_if(in_state(Screen::Gameplay)),
+    );
 }
 
+/// How long the run has been going, things get harder with it
 #[derive(Resource, Reflect, Debug, Default)]
 #[reflect(Resource)]
-struct DifficultySecs(f32);
+pub struct DifficultySecs(pub f32);
 
 fn reset_difficulty(mut dif: ResMut<DifficultySecs>) {
     dif.0 = 0.;
 }
 
+fn reset_spawn(mut timer: ResMut<WolfSpawnStatus>) {
+    *timer = WolfSpawnStatus::default();
+}
+
 fn update_difficulty(time: Res<Time>, mut dif: ResMut<DifficultySecs>) {
     dif.0 += time.delta_secs();
 }
@@ -68,7 +107,7 @@ impl FromWorld for WolfAssets {
             ),
             layout: assets.add(TextureAtlasLayout::from_grid(
                 UVec2::splat(24),
-                2,
+                5,
                 1,
                 None,
                 None,
@@ -94,15 +133,24 @@ pub struct WolfAssets {
 
 #[derive(Component, Reflect, Debug)]
 #[reflect(Component)]
+#[require(Velocity, Mass = Mass::WOLF)]
 pub struct Wolf {
+    state: WolfState,
     prey: Option<Entity>,
+    /// Until the next decision, or until the current state ends
     time_left: Timer,
+    pub role: Role,
+    /// Where to go on the way to the prey, see [`pack`], or where to wander while roaming
+    goal: Option<Vec2>,
 }
 
 impl Default for Wolf {
     fn default() -> Self {
         Self {
+            state: WolfState::Roam,
             prey: None,
+            role: Role::Alone,
+            goal: None,
             time_left: Timer::new(
                 Duration::from_secs_f32(THINK_INTERVAL_HUNGRY),
                 TimerMode::Repeating,
@@ -111,6 +159,60 @@ impl Default for Wolf {
     }
 }
 
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WolfState {
+    /// Wander around until it picks a prey
+    Roam,
+    /// Creep up on the prey
+    Stalk,
+    /// Run at the prey, speeding up but turning wide
+    Charge,
+    /// Catch its breath after a miss
+    GiveUp,
+    Eat,
+}
+
+impl WolfState {
+    /// Frame in `images/wolf.png`
+    const fn atlas_index(self) -> usize {
+        match self {
+            Self::Roam => 0,
+            Self::Eat => 1,
+            Self::Stalk => 2,
+            Self::Charge => 3,
+            Self::GiveUp => 4,
+        }
+    }
+}
+
+impl Wolf {
+    #[must_use]
+    pub const fn state(&self) -> WolfState {
+        self.state
+    }
+
+    #[must_use]
+    pub const fn prey(&self) -> Option<Entity> {
+        self.prey
+    }
+
+    fn enter(&mut self, state: WolfState, secs: f32) {
+        // The roaming goal isn't on the way to any prey
+        if self.state == WolfState::Roam {
+            self.goal = None;
+        }
+        self.state = state;
+        self.time_left.set_duration(Duration::from_secs_f32(secs));
+        self.time_left.reset();
+        if matches!(state, WolfState::Roam | WolfState::GiveUp | WolfState::Eat) {
+            self.prey = None;
+            self.goal = None;
+        }
+    }
+}
+
+/// A resource rather than a `Local`, so every run starts the same
+#[derive(Resource, Debug)]
 pub struct WolfSpawnStatus(Timer);
 
 #[cfg(feature = "dev")]
@@ -130,12 +232,14 @@ impl Default for WolfSpawnStatus {
 
 fn spawn(
     mut commands: Commands,
-    mut timer: Local<WolfSpawnStatus>,
+    mut timer: ResMut<WolfSpawnStatus>,
     time: Res<Time>,
     level: Query<Entity, With<Level>>,
     wolves: Query<(), With<Wolf>>,
     assets: If<Res<WolfAssets>>,
     dif: Res<DifficultySecs>,
+    kinds: Res<WolfKinds>,
+    mut rng: ResMut<GameRng>,
 ) {
     let Some(level) = level.iter().next() else {
         return;
@@ -154,37 +258,55 @@ fn spawn(
     }
 
     let transform = Transform {
-        translation: position_at_edge().0.extend(0.),
+        translation: position_at_edge(&mut *rng).0.extend(0.),
         scale: Vec2::splat(1.).extend(0.),
         ..Default::default()
     };
 
-    commands
-        .spawn((
-            Name::new("Wolf"),
-            transform,
-            Wolf::default(),
-            Sprite::from_atlas_image(
-                assets.wolf.clone(),
-                TextureAtlas {
-                    layout: assets.layout.clone(),
-                    index: 0,
-                },
-            ),
-            ChildOf(level),
-        ))
-        .with_child((
-            Transform {
-                translation: Vec3::new(0., 0., -1.),
-                scale: Vec2::splat(40.).extend(0.),
-                rotation: Quat::default(),
-            },
-            Mesh2d(assets.halo_mesh.clone()),
-            MeshMaterial2d(assets.halo_mat.clone()),
-        ));
+    // Tell them apart by their coat
+    let detective = count_wolves > 0 && rng.random::<f32>() < kinds.detective_chance;
+    let mut sprite = Sprite::from_atlas_image(
+        assets.wolf.clone(),
+        TextureAtlas {
+            layout: assets.layout.clone(),
+            index: 0,
+        },
+    );
+    if detective {
+        sprite.color = RESURRECT_PALETTE[7];
+    }
+
+    let mut wolf = commands.spawn((
+        Name::new(if detective { "Detective wolf" } else { "Wolf" }),
+        transform,
+        Interpolated::default(),
+        Wolf::default(),
+        sprite,
+        ChildOf(level),
+    ));
+    if detective {
+        wolf.insert(Detective);
+    }
+    wolf.with_child((
+        Transform {
+            translation: Vec3::new(0., 0., -1.),
+            scale: Vec2::splat(40.).extend(0.),
+            rotation: Quat::default(),
+        },
+        Mesh2d(assets.halo_mesh.clone()),
+        MeshMaterial2d(assets.halo_mat.clone()),
+    ));
 }
 
+/// A wolf's charge at the player's sheep went past it
+#[derive(Message, Debug)]
+pub struct NearMiss;
+
 const THINK_INTERVAL_HUNGRY: f32 = 0.5;
+const ROAM_SECS: f32 = 1.5;
+const GIVE_UP_SECS: f32 = 1.2;
+/// A charge that lasts longer than this is a miss
+const CHARGE_SECS: f32 = 2.;
 const SLEEP_TIME_INITIAL: f32 = 1.0;
 const SLEEP_HALF_TIME: f32 = 60.0;
 
@@ -193,16 +315,20 @@ fn sleep_time(elapsed_secs: f32) -> f32 {
 }
 
 const EAT_RANGE: f32 = 16.;
+/// How much closer a boosting player seems, when they stand out completely
+const BOOST_LURE: f32 = 150.;
 
 fn think_eat(
     mut commands: Commands,
     time: Res<Time>,
-    wolf: Query<(&Transform, &mut Wolf, &mut Sprite)>,
+    wolf: Query<(&Transform, &mut Wolf, Has<Detective>)>,
     sheep: Query<(Entity, &Transform, Option<&HumanMind>), With<Sheep>>,
+    suspicion: Query<(Entity, &Suspicion)>,
     mut next_screen: ResMut<NextState<Screen>>,
     dif: Res<DifficultySecs>,
+    boost: Res<Boost>,
 ) {
-    for (transf, mut wolf, mut sprite) in wolf {
+    for (transf, mut wolf, detective) in wolf {
         wolf.time_left.tick(time.delta());
 
         let pos = transf.translation.xy();
@@ -219,29 +345,77 @@ fn think_eat(
             return;
         };
 
-        if (dist as f32) < EAT_RANGE {
+        if (dist as f32) < EAT_RANGE && wolf.state != WolfState::Eat {
             commands.entity(id).despawn();
-            wolf.prey = None;
-            wolf.time_left
-                .set_duration(Duration::from_secs_f32(sleep_time(dif.0)));
-            wolf.time_left.reset();
-            if let Some(atlas) = sprite.texture_atlas.as_mut() {
-                atlas.index = 1;
-            } else {
-                error!("No atlas in wolf?");
-            }
+            wolf.enter(WolfState::Eat, sleep_time(dif.0));
             if human {
                 next_screen.set(Screen::GameOver);
             }
         } else if wolf.time_left.just_finished() {
-            wolf.time_left
-                .set_duration(Duration::from_secs_f32(THINK_INTERVAL_HUNGRY));
-            if let Some(atlas) = sprite.texture_atlas.as_mut() {
-                atlas.index = 0;
+            // A sheep sprinting away from a calm flock is hard to miss
+            let id = sheep
+                .iter()
+                .find(|(_, _, human)| human.is_some())
+                .filter(|(_, t, _)| {
+                    pos.distance(t.translation.xy()) - BOOST_LURE * boost.stand_out < dist as f32
+                })
+                .map_or(id, |(player, _, _)| player);
+            let id = if detective {
+                most_suspicious(suspicion).unwrap_or(id)
             } else {
-                error!("No atlas in wolf?");
+                id
+            };
+            match wolf.state {
+                WolfState::Eat | WolfState::GiveUp => wolf.enter(WolfState::Roam, ROAM_SECS),
+                WolfState::Roam => {
+                    wolf.enter(WolfState::Stalk, THINK_INTERVAL_HUNGRY);
+                    wolf.prey = Some(id);
+                }
+                // Keep an eye out for easier prey
+                WolfState::Stalk => wolf.prey = Some(id),
+                WolfState::Charge => wolf.enter(WolfState::GiveUp, GIVE_UP_SECS),
             }
-            wolf.prey = Some(id);
+        }
+    }
+}
+
+/// Wolves this close to a bleat go see who bleated
+const EARSHOT: f32 = 200.;
+/// Further for the player's bleat, which is louder, and which also turns stalking wolves around
+const PLAYER_EARSHOT: f32 = 300.;
+/// How long a wolf sticks to the bleating sheep before looking for easier prey
+const LURED_SECS: f32 = 2.;
+
+/// Roaming wolves come for the sheep that bleat, and the player's bleat gets the attention of
+/// those already hunting too
+fn hear(mut bleats: MessageReader<Bleated>, wolves: Query<(&Transform, &mut Wolf)>) {
+    let bleats: Vec<_> = bleats.read().collect();
+    for (transf, mut wolf) in wolves {
+        let pos = transf.translation.xy();
+        let Some(bleat) = bleats
+            .iter()
+            .filter(|bleat| {
+                let earshot = if bleat.from_player {
+                    PLAYER_EARSHOT
+                } else {
+                    EARSHOT
+                };
+                pos.distance(bleat.pos) <= earshot
+            })
+            // The player is the one to listen to
+            .max_by_key(|bleat| bleat.from_player)
+        else {
+            continue;
+        };
+        let lured = match wolf.state {
+            WolfState::Roam => true,
+            WolfState::Stalk => bleat.from_player && wolf.prey != Some(bleat.sheep),
+            WolfState::Charge | WolfState::GiveUp | WolfState::Eat => false,
+        };
+        if lured {
+            wolf.enter(WolfState::Stalk, LURED_SECS);
+            wolf.prey = Some(bleat.sheep);
+            wolf.goal = None;
         }
     }
 }
@@ -255,23 +429,134 @@ fn speed(elapsed_secs: f32) -> f32 {
         .clamp(INITIAL_SPEED, MAX_SPEED)
 }
 
+fn most_suspicious(suspicion: Query<(Entity, &Suspicion)>) -> Option<Entity> {
+    suspicion
+        .iter()
+        .max_by(|a, b| a.1.score.total_cmp(&b.1.score))
+        .map(|(id, _)| id)
+}
+
+/// A wolf this close to a sheep bumps into it
+const BUMP_RANGE: f32 = 20.;
+/// Sheep this close to a wolf count towards the crowd holding it back
+const CROWD_RANGE: f32 = 40.;
+
+/// Dense clusters of sheep shove wolves back out, a few sheep alone can't
+fn push_back(grid: Res<SheepGrid>, wolves: Query<(&Mass, &mut Transform), With<Wolf>>) {
+    for (mass, mut transf) in wolves {
+        let pos = transf.translation.xy();
+        let Some(closest) = grid
+            .near(pos, false, BUMP_RANGE)
+            .map(|(_, offset)| offset)
+            .min_by(|a, b| a.length().total_cmp(&b.length()))
+        else {
+            continue;
+        };
+        let crowd = grid.near(pos, false, CROWD_RANGE).count() as f32 * Mass::SHEEP.0;
+        let share = ((crowd - mass.0) / crowd).max(0.);
+        let need_dist = BUMP_RANGE - closest.length();
+        transf.translation -= (share * need_dist * closest.normalize_or_zero()).extend(0.);
+    }
+}
+
+const ROAM_SPEED: f32 = 40.;
+/// Roaming wolves wander to somewhere this far from the middle of the field
+const ROAM_RADIUS: f32 = 100.;
+/// And pick somewhere else once they're this close
+const ROAM_REACHED: f32 = 30.;
+const STALK_SPEED: f32 = 50.;
+/// Trot up to the prey until this close, then stalk
+const STALK_RANGE: f32 = 150.;
+const CHARGE_RANGE: f32 = 80.;
+/// Top charge speed, as a multiple of [`speed`]
+const CHARGE_FACTOR: f32 = 2.2;
+const CHARGE_ACCELERATION: f32 = 400.;
+const TURN_RADIUS: f32 = 80.;
+/// The charge fails once the prey is this far off to the side, in radians
+const MISS_ANGLE: f32 = PI / 3.;
+/// Running past the player's sheep this close is a [`NearMiss`]
+const NEAR_MISS_RANGE: f32 = 40.;
+const DECELERATION: f32 = 300.;
+/// In radians per second, outside of a charge
+const TURN_RATE: f32 = 8.;
+
 fn hunt(
     time: Res<Time>,
-    wolf: Query<(&mut Transform, &mut Wolf)>,
-    sheep: Query<&Transform, Without<Wolf>>,
+    wolf: Query<(&mut Transform, &mut Wolf, &mut Velocity)>,
+    sheep: Query<(&Transform, Has<HumanMind>), Without<Wolf>>,
     dif: Res<DifficultySecs>,
+    mut rng: ResMut<GameRng>,
+    mut near_miss: MessageWriter<NearMiss>,
 ) {
-    for (mut transform, mut think) in wolf {
-        let Some(prey) = think.prey else {
-            continue;
-        };
-        let Ok(prey) = sheep.get(prey) else {
-            think.prey = None;
-            continue;
-        };
-        let target = (prey.translation - transform.translation).normalize_or_zero();
+    let dt = time.delta_secs();
+    for (mut transform, mut wolf, mut velocity) in wolf {
+        let pos = transform.translation.xy();
+        let (prey, prey_is_player) = wolf
+            .prey
+            .and_then(|prey| sheep.get(prey).ok())
+            .map_or((None, false), |(prey, human)| {
+                (Some(prey.translation.xy()), human)
+            });
+
+        match (wolf.state, prey) {
+            (WolfState::Roam, _) => {
+                // Wander, but stay around the field
+                let wander = match wolf.goal {
+                    Some(goal) if goal.distance(pos) > ROAM_REACHED => goal,
+                    _ => *wolf
+                        .goal
+                        .insert(Vec2::from_angle(rng.random_range(-PI..PI)) * ROAM_RADIUS),
+                };
+                velocity.speed = ROAM_SPEED;
+                velocity.turn_towards(wander - pos, TURN_RATE * dt / 4.);
+            }
+            (WolfState::Stalk, Some(prey)) => {
+                let dist = pos.distance(prey);
+                if wolf.goal.is_none() && dist < CHARGE_RANGE {
+                    wolf.enter(WolfState::Charge, CHARGE_SECS);
+                }
+                // Flankers hurry to get in place
+                velocity.speed = if wolf.goal.is_some() || dist > STALK_RANGE {
+                    speed(dif.0)
+                } else {
+                    STALK_SPEED
+                };
+                let goal = wolf.goal.unwrap_or(prey);
+                velocity.turn_towards(goal - pos, TURN_RATE * dt);
+            }
+            (WolfState::Charge, Some(prey)) => {
+                let to_prey = prey - pos;
+                velocity.speed =
+                    (velocity.speed + CHARGE_ACCELERATION * dt).min(CHARGE_FACTOR * speed(dif.0));
+                let max_turn = velocity.speed / TURN_RADIUS * dt;
+                velocity.turn_towards(to_prey, max_turn);
+                // The prey dodged, or it ran past
+                if velocity.heading.angle_to(to_prey).abs() > MISS_ANGLE
+                    && to_prey.length() > EAT_RANGE
+                {
+                    wolf.enter(WolfState::GiveUp, GIVE_UP_SECS);
+                    if prey_is_player && to_prey.length() < NEAR_MISS_RANGE {
+                        near_miss.write(NearMiss);
+                    }
+                }
+            }
+            (WolfState::Stalk | WolfState::Charge, None) => {
+                wolf.enter(WolfState::Roam, ROAM_SECS);
+            }
+            (WolfState::GiveUp | WolfState::Eat, _) => {
+                velocity.speed = (velocity.speed - DECELERATION * dt).max(0.);
+            }
+        }
 
-        transform.translation += target * speed(dif.0) * time.delta_secs();
+        transform.translation += (velocity.get() * dt).extend(0.);
+    }
+}
+
+fn update_sprite(wolves: Query<(&Wolf, &mut Sprite), Changed<Wolf>>) {
+    for (wolf, mut sprite) in wolves {
+        if let Some(atlas) = sprite.texture_atlas.as_mut() {
+            atlas.index = wolf.state.atlas_index();
+        }
     }
 }
 
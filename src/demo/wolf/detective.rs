@@ -0,0 +1,123 @@
+//! A wolf that hunts the sheep that don't move like sheep.
+//!
+//! Sheep stop every now and then, turn gradually and only run when there's a wolf around.
+//! Every sheep gets a [`Suspicion`] score for breaking those habits, and detectives go after the
+//! highest one instead of the closest.
+
+use bevy::prelude::*;
+
+use crate::{
+    camera::GAME_WIDTH,
+    demo::{
+        sheep::{Fear, Sheep, SheepMind, boids::Boids},
+        wolf::Wolf,
+    },
+};
+
+/// Hunts by [`Suspicion`] instead of by distance
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct Detective;
+
+/// How often wolves are detectives
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct WolfKinds {
+    /// Chance for each new wolf after the first, 0 to turn detectives off
+    pub detective_chance: f32,
+}
+
+impl Default for WolfKinds {
+    fn default() -> Self {
+        Self {
+            detective_chance: 0.25,
+        }
+    }
+}
+
+/// How un-sheep-like a sheep has been moving lately, from 0 to 1
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct Suspicion {
+    pub score: f32,
+    last_pos: Option<Vec2>,
+    last_velocity: Vec2,
+    moving_secs: f32,
+}
+
+/// How far back detectives remember, roughly
+const WINDOW_SECS: f32 = 5.;
+/// Faster than any calm sheep walks, unless it means to
+const WALK_SPEED_MAX: f32 = 110.;
+/// Sheep always stop before this
+const MOVING_SECS_MAX: f32 = 1.2;
+/// Slower than this counts as standing, collisions jostle sheep a bit
+const MOVING_SPEED_MIN: f32 = 30.;
+/// How many habits there are to break
+const HABITS: f32 = 3.;
+
+pub(super) fn add_suspicion(
+    mut commands: Commands,
+    sheep: Query<Entity, (With<Sheep>, Without<Suspicion>)>,
+) {
+    for id in sheep {
+        commands.entity(id).insert(Suspicion::default());
+    }
+}
+
+pub(super) fn watch(
+    time: Res<Time>,
+    fear: Res<Fear>,
+    boids: Res<Boids>,
+    sheep: Query<(&Transform, Option<&SheepMind>, &mut Suspicion)>,
+    wolves: Query<&Transform, With<Wolf>>,
+) {
+    let dt = time.delta_secs();
+    if dt == 0. {
+        return;
+    }
+
+    for (transf, mind, mut suspicion) in sheep {
+        let pos = transf.translation.xy();
+        let last_pos = suspicion.last_pos.replace(pos);
+        // Skip the first sighting and wrapping around the screen
+        let Some(last_pos) = last_pos.filter(|last| last.distance(pos) < GAME_WIDTH / 2.) else {
+            continue;
+        };
+        let velocity = (pos - last_pos) / dt;
+        let speed = velocity.length();
+
+        let mut odd = 0.;
+
+        let scared = wolves
+            .iter()
+            .any(|wolf| wolf.translation.xy().distance(pos) <= fear.radius);
+        let speed_max = mind.map_or(WALK_SPEED_MAX, |mind| {
+            WALK_SPEED_MAX.max(mind.intended_speed())
+        });
+        if speed > speed_max && !scared {
+            odd += 1.;
+        }
+
+        if speed > MOVING_SPEED_MIN {
+            suspicion.moving_secs += dt;
+        } else {
+            suspicion.moving_secs = 0.;
+        }
+        if suspicion.moving_secs > MOVING_SECS_MAX {
+            odd += 1.;
+        }
+
+        let last_velocity = suspicion.last_velocity;
+        if speed > MOVING_SPEED_MIN && last_velocity.length() > MOVING_SPEED_MIN {
+            let turn_rate = last_velocity.angle_to(velocity).abs() / dt;
+            if turn_rate > 1.5 * boids.max_turn_rate {
+                odd += 1.;
+            }
+        }
+        suspicion.last_velocity = velocity;
+
+        let target = odd / HABITS;
+        suspicion.score += (target - suspicion.score) * (dt / WINDOW_SECS).min(1.);
+    }
+}
@@ -0,0 +1,140 @@
+//! Once there's more than one hungry wolf, they hunt together.
+//!
+//! The pack agrees on a sheep that's easy to cut off from the flock. The closest wolf drives it,
+//! and the others circle around to the flock's side so it can't run back to safety.
+//! [`Detective`]s hunt on their own.
+
+use bevy::prelude::*;
+
+use crate::demo::{
+    sheep::{Sheep, grid::SheepGrid},
+    wolf::{Wolf, WolfState, detective::Detective},
+};
+
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct Pack {
+    target: Option<Entity>,
+    chase_secs: f32,
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Role {
+    /// Not hunting with others
+    #[default]
+    Alone,
+    /// Chases the target straight on
+    Driver,
+    /// Gets between the target and the flock
+    Flanker,
+}
+
+/// Look for another target if the chase takes longer than this
+const GIVE_UP_SECS: f32 = 8.;
+/// How far around a sheep the pack looks for its flock
+const FLOCK_RANGE: f32 = 80.;
+/// How much being surrounded makes a sheep less appealing, in units of distance
+const CROWD_COST: f32 = 40.;
+/// How far from the target flankers wait
+const FLANK_DIST: f32 = 50.;
+/// Angle between flankers, in radians
+const FLANK_SPREAD: f32 = 0.6;
+
+pub(super) fn reset(mut pack: ResMut<Pack>) {
+    *pack = Pack::default();
+}
+
+pub(super) fn plan(
+    mut pack: ResMut<Pack>,
+    time: Res<Time>,
+    grid: Res<SheepGrid>,
+    wolves: Query<(&Transform, &mut Wolf), Without<Detective>>,
+    sheep: Query<(Entity, &Transform), With<Sheep>>,
+) {
+    let mut hunters: Vec<_> = wolves
+        .into_iter()
+        .filter(|(_, wolf)| wolf.prey.is_some())
+        .collect();
+
+    if hunters.len() < 2 {
+        for (_, wolf) in &mut hunters {
+            wolf.role = Role::Alone;
+            wolf.goal = None;
+        }
+        pack.target = None;
+        return;
+    }
+
+    let pack_center = hunters
+        .iter()
+        .map(|(transf, _)| transf.translation.xy())
+        .sum::<Vec2>()
+        / hunters.len() as f32;
+
+    pack.chase_secs += time.delta_secs();
+    let target_alive = pack.target.is_some_and(|target| sheep.contains(target));
+    if !target_alive || pack.chase_secs > GIVE_UP_SECS {
+        pack.target = sheep
+            .iter()
+            .map(|(id, transf)| {
+                let crowd = grid.neighbors(id, FLOCK_RANGE).count() as f32;
+                let cost = transf.translation.xy().distance(pack_center) + CROWD_COST * crowd;
+                (id, cost)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id);
+        pack.chase_secs = 0.;
+    }
+    let Some((target, target_transf)) = pack.target.and_then(|target| sheep.get(target).ok())
+    else {
+        return;
+    };
+    let target_pos = target_transf.translation.xy();
+
+    hunters.sort_by(|a, b| {
+        let a = a.0.translation.xy().distance(target_pos);
+        let b = b.0.translation.xy().distance(target_pos);
+        a.total_cmp(&b)
+    });
+
+    // Which way the sheep would run to rejoin the others
+    let (count, sum) = grid
+        .neighbors(target, FLOCK_RANGE)
+        .fold((0, Vec2::ZERO), |(count, sum), (_, offset)| {
+            (count + 1, sum + offset)
+        });
+    let driver_pos = hunters[0].0.translation.xy();
+    let to_safety = if count > 0 {
+        sum.normalize_or_zero()
+    } else {
+        // Nowhere to go, so just get ahead of it
+        (target_pos - driver_pos).normalize_or_zero()
+    };
+
+    for (i, (transf, wolf)) in hunters.iter_mut().enumerate() {
+        // Too late to change course
+        if wolf.state != WolfState::Charge {
+            wolf.prey = Some(target);
+        }
+        if i == 0 {
+            wolf.role = Role::Driver;
+            wolf.goal = None;
+            continue;
+        }
+
+        // 0, 1, -1, 2, -2...
+        let flanker = i - 1;
+        let side = flanker.div_ceil(2) as f32 * if flanker % 2 == 0 { -1. } else { 1. };
+        let flank =
+            target_pos + FLANK_DIST * Vec2::from_angle(side * FLANK_SPREAD).rotate(to_safety);
+        let pos = transf.translation.xy();
+        wolf.role = Role::Flanker;
+        // Once in position, close in
+        wolf.goal =
+            if pos.distance(flank) > FLANK_DIST / 3. && pos.distance(target_pos) > FLANK_DIST {
+                Some(flank)
+            } else {
+                None
+            };
+    }
+}
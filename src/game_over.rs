@@ -2,14 +2,14 @@ use bevy::prelude::*;
 
 use crate::{
     asset_tracking::ResourceHandles, demo::cabbage::Score, intro::PlayedIntro,
-    menus::start_already, screens::Screen, theme::widget,
+    menus::start_already, rng::GameRng, screens::Screen, theme::widget,
 };
 
 pub fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::GameOver), spawn);
 }
 
-fn spawn(mut commands: Commands, score: Res<Score>) {
+fn spawn(mut commands: Commands, score: Res<Score>, rng: Res<GameRng>) {
     commands.spawn((
         widget::ui_root("Game Over"),
         GlobalZIndex(2),
@@ -17,6 +17,7 @@ fn spawn(mut commands: Commands, score: Res<Score>) {
         children![
             widget::header("Game Over"),
             widget::label(format!("We ate {} cabbage", score.0)),
+            widget::label(format!("Seed: {}", rng.seed())),
             widget::button("Restart", restart),
             widget::button("Main Menu", to_menu),
         ],
@@ -0,0 +1,50 @@
+//! Run the gameplay without a window, audio or renderer, e.g. for tests.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::{input::InputPlugin, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+use crate::{
+    GameplayPlugin,
+    demo::{
+        cabbage::CabbageAssets, level::LevelAssets, player::PlayerAssets, sheep::SheepAssets,
+        wolf::WolfAssets,
+    },
+    screens::Screen,
+};
+
+/// How much time passes on every `app.update()`, which is exactly one `FixedUpdate` tick
+pub const TICK: Duration = Duration::from_micros(15_625);
+
+/// An app with only the gameplay plugins, starting on [`Screen::Splash`].
+/// Set the next [`Screen`] to start a run.
+pub fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        StatesPlugin,
+        InputPlugin,
+        AssetPlugin::default(),
+    ));
+    // These are normally registered by the render and audio plugins
+    app.init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<Mesh>()
+        .init_asset::<AudioSource>();
+    app.init_resource::<ClearColor>();
+    app.init_state::<Screen>();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(TICK));
+    app.insert_resource(Time::<Fixed>::from_duration(TICK));
+
+    app.add_plugins(GameplayPlugin);
+
+    // There are no loaders for images or sound, so don't wait for them to load
+    app.init_resource::<PlayerAssets>()
+        .init_resource::<SheepAssets>()
+        .init_resource::<WolfAssets>()
+        .init_resource::<CabbageAssets>()
+        .init_resource::<LevelAssets>();
+
+    app
+}
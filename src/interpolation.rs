@@ -0,0 +1,112 @@
+//! Smooth out movement that happens in `FixedUpdate`.
+//!
+//! Between fixed ticks, an [`Interpolated`] entity is drawn between where it was on the last two
+//! ticks, so it doesn't stutter when the frame rate doesn't match the tick rate.
+
+use bevy::prelude::*;
+
+use crate::camera::{GAME_HEIGHT, GAME_WIDTH};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        RunFixedMainLoop,
+        (
+            restore.in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop),
+            interpolate.in_set(RunFixedMainLoopSystems::AfterFixedMainLoop),
+        ),
+    );
+    app.add_systems(FixedFirst, remember_previous);
+}
+
+/// Put this on entities whose [`Transform`] is moved in `FixedUpdate`.
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct Interpolated {
+    previous: Option<Vec3>,
+    current: Option<Vec3>,
+}
+
+/// Put back the real translation before the simulation sees it
+fn restore(query: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transf, interpolated) in query {
+        if let Some(current) = interpolated.current {
+            transf.translation = current;
+        }
+    }
+}
+
+fn remember_previous(query: Query<(&Transform, &mut Interpolated)>) {
+    for (transf, mut interpolated) in query {
+        interpolated.previous = Some(transf.translation);
+    }
+}
+
+fn interpolate(time: Res<Time<Fixed>>, query: Query<(&mut Transform, &mut Interpolated)>) {
+    let fraction = time.overstep_fraction();
+    for (mut transf, mut interpolated) in query {
+        let current = transf.translation;
+        interpolated.current = Some(current);
+        let Some(previous) = interpolated.previous else {
+            continue;
+        };
+        // Don't slide across the screen when wrapping around it
+        let jump = (current - previous).abs();
+        if jump.x > GAME_WIDTH / 2. || jump.y > GAME_HEIGHT / 2. {
+            continue;
+        }
+        transf.translation = previous.lerp(current, fraction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    const STEP: f32 = 10.;
+
+    fn step(query: Query<&mut Transform, With<Interpolated>>) {
+        for mut transf in query {
+            transf.translation.x += STEP;
+        }
+    }
+
+    #[test]
+    fn drawn_between_the_last_two_ticks() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, plugin));
+        app.add_systems(FixedUpdate, step);
+        // Four frames for every tick
+        let tick = Duration::from_millis(40);
+        app.insert_resource(Time::<Fixed>::from_duration(tick));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(tick / 4));
+        let entity = app
+            .world_mut()
+            .spawn((Transform::default(), Interpolated::default()))
+            .id();
+
+        let mut drawn = Vec::new();
+        for _ in 0..16 {
+            app.update();
+            drawn.push(app.world().get::<Transform>(entity).unwrap().translation.x);
+        }
+        let simulated = app
+            .world()
+            .get::<Interpolated>(entity)
+            .unwrap()
+            .current
+            .unwrap()
+            .x;
+
+        // It moves a bit every frame, not a whole step every fourth frame
+        assert!(drawn.windows(2).skip(8).all(|pair| {
+            let moved = pair[1] - pair[0];
+            moved > 0. && moved < STEP
+        }));
+        let last = drawn.last().unwrap();
+        assert!(*last < simulated && *last > simulated - STEP);
+    }
+}
@@ -5,10 +5,8 @@ use bevy::prelude::*;
 use crate::{
     PausableSystems,
     demo::{
-        cabbage::{Cabbage, Score},
-        level::{BG_COLOR, Level, N_SHEEP},
-        player::PlayerAssets,
-        sheep::{Sheep, new_sheep},
+        level::{BG_COLOR, Level},
+        sheep::bleat::BleatWave,
     },
     intro::{message::MESSAGES, text_fade::FadeIn},
     screens::Screen,
@@ -221,23 +219,14 @@ fn resume(
     }
 }
 
+/// The run gets a fresh level, spawned after the run's seed is picked so it can be reproduced
 fn reset_and_start(
     mut commands: Commands,
-    // level_assets: Res<LevelAssets>,
-    player_assets: Res<PlayerAssets>,
     level: Single<Entity, With<Level>>,
-    sheep: Query<(), With<Sheep>>,
-    cabbage: Query<Entity, With<Cabbage>>,
-    mut score: ResMut<Score>,
+    waves: Query<Entity, With<BleatWave>>,
 ) {
-    let sheep = sheep.count();
-    for _ in sheep..N_SHEEP {
-        commands.spawn((new_sheep(&player_assets, Screen::Gameplay), ChildOf(*level)));
-    }
-
-    for id in cabbage {
+    commands.entity(*level).despawn();
+    for id in waves {
         commands.entity(id).despawn();
     }
-
-    score.0 = 0;
 }
@@ -0,0 +1,133 @@
+// Support configuring Bevy lints within code.
+#![cfg_attr(bevy_lint, feature(register_tool), register_tool(bevy))]
+#![warn(clippy::pedantic, clippy::nursery)]
+#![allow(
+    clippy::needless_pass_by_value,
+    clippy::suboptimal_flops,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation,
+    // This one is just bugged
+    clippy::used_underscore_binding
+)]
+
+mod asset_tracking;
+mod audio;
+mod camera;
+pub mod controls;
+pub mod demo;
+#[cfg(feature = "dev")]
+mod dev_tools;
+mod game_over;
+pub mod headless;
+mod interpolation;
+mod intro;
+mod menus;
+mod replay;
+pub mod rng;
+pub mod screens;
+mod settings;
+mod theme;
+
+use bevy::{asset::AssetMetaCheck, ecs::schedule::ScheduleLabel, prelude::*};
+
+pub struct AppPlugin;
+
+impl Plugin for AppPlugin {
+    fn build(&self, app: &mut App) {
+        // Add Bevy plugins.
+        app.add_plugins(
+            DefaultPlugins
+                .set(AssetPlugin {
+                    // Wasm builds will check for meta files (that don't exist) if this isn't set.
+                    // This causes errors and even panics on web build on itch.
+                    // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
+                    meta_check: AssetMetaCheck::Never,
+                    ..default()
+                })
+                .set(WindowPlugin {
+                    primary_window: Window {
+                        title: "Which Sheep".to_string(),
+                        fit_canvas_to_parent: true,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+                .set(ImagePlugin::default_nearest()),
+        );
+
+        // Add other plugins.
+        app.add_plugins((
+            GameplayPlugin,
+            audio::plugin,
+            #[cfg(feature = "dev")]
+            dev_tools::plugin,
+            menus::plugin,
+            screens::plugin,
+            theme::plugin,
+            camera::plugin,
+            replay::plugin,
+            interpolation::plugin,
+            settings::plugin,
+        ));
+    }
+}
+
+/// The game simulation, without anything that needs a window, audio or a renderer.
+/// See [`headless::app`] to run it on its own.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            asset_tracking::plugin,
+            demo::plugin,
+            game_over::plugin,
+            controls::plugin,
+            intro::plugin,
+            rng::plugin,
+        ));
+
+        // Order new `AppSystems` variants by adding them here:
+        for schedule in [Update.intern(), FixedUpdate.intern()] {
+            app.configure_sets(
+                schedule,
+                (
+                    AppSystems::TickTimers,
+                    AppSystems::RecordInput,
+                    AppSystems::Update,
+                )
+                    .chain(),
+            );
+        }
+
+        // Set up the `Pause` state.
+        app.init_state::<Pause>();
+        for schedule in [Update.intern(), FixedUpdate.intern()] {
+            app.configure_sets(schedule, PausableSystems.run_if(in_state(Pause(false))));
+        }
+    }
+}
+
+/// High-level groupings of systems for the app in the `Update` and `FixedUpdate` schedules.
+/// The simulation runs in `FixedUpdate`, so it plays the same at any frame rate.
+/// When adding a new variant, make sure to order it in the `configure_sets`
+/// call above.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+enum AppSystems {
+    /// Tick timers.
+    TickTimers,
+    /// Record player input.
+    RecordInput,
+    /// Do everything else (consider splitting this into further variants).
+    Update,
+}
+
+/// Whether or not the game is paused.
+#[derive(States, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+struct Pause(pub bool);
+
+/// A system set for systems that shouldn't run while the game is paused.
+#[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct PausableSystems;
@@ -1,11 +1,15 @@
 //! The settings menu.
 //!
 //! Additional settings and accessibility options should go here.
+//!
+//! Every action has a primary and a secondary key, and a gamepad button. Picking something that's
+//! already in use asks whether to swap the two, and Escape and P are kept for pausing.
 
 use bevy::{
     input::{
         ButtonState,
         common_conditions::input_just_pressed,
+        gamepad::GamepadButtonStateChangedEvent,
         keyboard::{self, KeyboardInput},
     },
     prelude::*,
@@ -13,16 +17,23 @@ use bevy::{
 
 use crate::{menus::Menu, screens::Screen, theme::prelude::*};
 
-use PlayerAction::{Bleat, Down, Left, Right, Up};
+use PlayerAction::{Assist, Bleat, Boost, Down, Jump, Left, Right, Up};
 
 pub(super) fn plugin(app: &mut App) {
-    app.init_resource::<ControlScheme>();
     app.add_systems(OnEnter(Menu::Controls), (spawn_menu, update_labels).chain());
+    app.add_systems(OnExit(Menu::Controls), close_prompt);
     app.add_systems(
         Update,
-        go_back.run_if(in_state(Menu::Controls).and(input_just_pressed(KeyCode::Escape))),
+        (
+            // Escape only cancels while picking a key
+            go_back.run_if(input_just_pressed(KeyCode::Escape).and(not(rebinding))),
+            cancel.run_if(input_just_pressed(KeyCode::Escape).and(rebinding)),
+            execute_change_key,
+            execute_change_button,
+        )
+            .chain()
+            .run_if(in_state(Menu::Controls)),
     );
-    app.add_systems(Update, execute_change_key.run_if(in_state(Menu::Controls)));
 
     app.add_systems(
         Update,
@@ -30,10 +41,6 @@ pub(super) fn plugin(app: &mut App) {
             .run_if(in_state(Menu::Controls))
             .run_if(resource_changed::<ControlScheme>),
     );
-    // app.add_systems(
-    //     Update,
-    //     update_global_volume_label.run_if(in_state(Menu::Settings)),
-    // );
 }
 
 fn spawn_menu(mut commands: Commands) {
@@ -44,7 +51,18 @@ fn spawn_menu(mut commands: Commands) {
         children![
             widget::header("Customize controls"),
             grid(),
-            widget::button("Back", go_back_on_click),
+            (widget::label(""), Notice),
+            (
+                Name::new("Bottom Row"),
+                Node {
+                    column_gap: px(30),
+                    ..default()
+                },
+                children![
+                    widget::narrow_button_with_bundle("Defaults", reset, (), ()),
+                    widget::narrow_button_with_bundle("Back", go_back_on_click, (), ()),
+                ],
+            ),
         ],
     ));
 }
@@ -56,24 +74,64 @@ fn grid() -> impl Bundle {
             display: Display::Grid,
             row_gap: px(10),
             column_gap: px(30),
-            grid_template_columns: RepeatedGridTrack::px(2, 400.0),
+            grid_template_columns: vec![
+                GridTrack::px(200.0),
+                GridTrack::px(240.0),
+                GridTrack::px(240.0),
+                GridTrack::px(240.0),
+            ],
             ..default()
         },
         children![
             Up.label(),
-            Up.key_change(),
+            Up.key_change(Slot::Primary),
+            Up.key_change(Slot::Secondary),
+            Up.key_change(Slot::Gamepad),
             Down.label(),
-            Down.key_change(),
+            Down.key_change(Slot::Primary),
+            Down.key_change(Slot::Secondary),
+            Down.key_change(Slot::Gamepad),
             Left.label(),
-            Left.key_change(),
+            Left.key_change(Slot::Primary),
+            Left.key_change(Slot::Secondary),
+            Left.key_change(Slot::Gamepad),
             Right.label(),
-            Right.key_change(),
+            Right.key_change(Slot::Primary),
+            Right.key_change(Slot::Secondary),
+            Right.key_change(Slot::Gamepad),
             Bleat.label(),
-            Bleat.key_change(),
+            Bleat.key_change(Slot::Primary),
+            Bleat.key_change(Slot::Secondary),
+            Bleat.key_change(Slot::Gamepad),
+            Jump.label(),
+            Jump.key_change(Slot::Primary),
+            Jump.key_change(Slot::Secondary),
+            Jump.key_change(Slot::Gamepad),
+            Assist.label(),
+            Assist.key_change(Slot::Primary),
+            Assist.key_change(Slot::Secondary),
+            Assist.key_change(Slot::Gamepad),
+            Boost.label(),
+            Boost.key_change(Slot::Primary),
+            Boost.key_change(Slot::Secondary),
+            Boost.key_change(Slot::Gamepad),
+            deadzone_label(),
+            widget::narrow_button_with_bundle("-", lower_deadzone, (), ()),
+            widget::narrow_button_with_bundle("+", raise_deadzone, (), ()),
         ],
     )
 }
 
+/// Which binding of an action a button in the controls menu changes
+#[derive(Clone, Copy, Reflect, Debug, PartialEq, Eq)]
+pub enum Slot {
+    Primary,
+    Secondary,
+    Gamepad,
+}
+
+const SLOTS: [Slot; 3] = [Slot::Primary, Slot::Secondary, Slot::Gamepad];
+
 #[derive(Clone, Copy, Reflect, Debug, PartialEq, Eq)]
 pub enum PlayerAction {
     Up,
@@ -81,6 +139,12 @@ pub enum PlayerAction {
     Left,
     Right,
     Bleat,
+    /// Spend the jump meter to switch sheep early
+    Jump,
+    /// Show which sheep the player is
+    Assist,
+    /// Run faster while there's stamina left
+    Boost,
 }
 
 impl PlayerAction {
@@ -91,6 +155,9 @@ impl PlayerAction {
             Left => "Left",
             Right => "Right",
             Bleat => "Bleat",
+            Jump => "Jump",
+            Assist => "Find me",
+            Boost => "Boost",
         }
     }
 
@@ -104,35 +171,23 @@ impl PlayerAction {
         )
     }
 
-    fn key_change(self) -> impl Bundle {
+    fn key_change(self, slot: Slot) -> impl Bundle {
         (
-            Name::new(format!("{} button", self.str())),
+            Name::new(format!("{} {slot:?} button", self.str())),
             Node {
                 justify_self: JustifySelf::Start,
                 ..default()
             },
-            children![
-                widget::button_with_bundle(
-                    "",
-                    start_change_key,
-                    KeyChange {
-                        which: self,
-                        changing: false
-                    },
-                    KeyLabel { which: self }
-                ),
-                // widget::button_small("-", lower_global_volume),
-                // (
-                //     Name::new("Current Volume"),
-                //     Node {
-                //         padding: UiRect::horizontal(px(10)),
-                //         justify_content: JustifyContent::Center,
-                //         ..default()
-                //     },
-                //     children![(widget::label(""), GlobalVolumeLabel)],
-                // ),
-                // widget::button_small("+", raise_global_volume),
-            ],
+            children![widget::narrow_button_with_bundle(
+                "",
+                start_change_key,
+                KeyChange {
+                    which: self,
+                    slot,
+                    changing: false
+                },
+                KeyLabel { which: self, slot }
+            )],
         )
     }
 }
@@ -141,19 +196,47 @@ impl PlayerAction {
 #[reflect(Component)]
 struct KeyLabel {
     which: PlayerAction,
+    slot: Slot,
 }
 
 #[derive(Component, Reflect, Debug)]
 #[reflect(Component)]
 struct KeyChange {
     which: PlayerAction,
+    slot: Slot,
     changing: bool,
 }
 
+/// Says why a key wasn't taken
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct Notice;
+
+/// Only there while the player decides what to do about a key that's already in use
+#[derive(Resource, Debug)]
+struct Conflict {
+    which: PlayerAction,
+    slot: Slot,
+    control: Control,
+    /// Where `control` is used now
+    other: PlayerAction,
+    other_slot: Slot,
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct ConflictPrompt;
+
+/// Whether a key is being picked, so Escape shouldn't leave the menu
+fn rebinding(buttons: Query<&KeyChange>, conflict: Option<Res<Conflict>>) -> bool {
+    conflict.is_some() || buttons.iter().any(|button| button.changing)
+}
+
 fn start_change_key(
     event: On<Pointer<Click>>,
     buttons: Query<(Entity, &mut KeyChange)>,
     labels: Query<(&mut Text, &KeyLabel), Without<KeyChange>>,
+    conflict: Option<Res<Conflict>>,
 ) {
     let this_id = event.original_event_target();
 
@@ -162,108 +245,537 @@ fn start_change_key(
         return;
     };
 
-    let mut code = None;
-    if button.changing {
+    if button.changing || conflict.is_some() {
         return;
     }
+    let (which, slot) = (button.which, button.slot);
     for (button_id, mut state) in buttons {
         state.changing = button_id == this_id;
-        if button_id == this_id {
-            code = Some(state.which);
-        }
     }
 
-    if let Some(code) = code {
-        for (mut text, label) in labels {
-            if label.which == code {
-                text.0 = "Press a key...".into();
+    for (mut text, label) in labels {
+        if label.which == which && label.slot == slot {
+            text.0 = match slot {
+                Slot::Primary | Slot::Secondary => "Press a key...",
+                Slot::Gamepad => "Press a button...",
             }
+            .into();
         }
     }
 }
 
+/// Stop picking a key, or answer "Cancel" to the prompt
+fn cancel(
+    mut commands: Commands,
+    mut buttons: Query<&mut KeyChange>,
+    prompt: Query<Entity, With<ConflictPrompt>>,
+    mut scheme: ResMut<ControlScheme>,
+) {
+    for mut button in &mut buttons {
+        button.changing = false;
+    }
+    for id in prompt {
+        commands.entity(id).despawn();
+    }
+    commands.remove_resource::<Conflict>();
+    // Put the labels back
+    scheme.set_changed();
+}
+
+fn cancel_on_click(
+    _: On<Pointer<Click>>,
+    commands: Commands,
+    buttons: Query<&mut KeyChange>,
+    prompt: Query<Entity, With<ConflictPrompt>>,
+    scheme: ResMut<ControlScheme>,
+) {
+    cancel(commands, buttons, prompt, scheme);
+}
+
+fn swap(
+    _: On<Pointer<Click>>,
+    mut commands: Commands,
+    conflict: Option<Res<Conflict>>,
+    prompt: Query<Entity, With<ConflictPrompt>>,
+    mut scheme: ResMut<ControlScheme>,
+) {
+    if let Some(conflict) = conflict {
+        scheme.swap(
+            conflict.which,
+            conflict.slot,
+            conflict.control.clone(),
+            conflict.other,
+            conflict.other_slot,
+        );
+    }
+    for id in prompt {
+        commands.entity(id).despawn();
+    }
+    commands.remove_resource::<Conflict>();
+}
+
+fn close_prompt(mut commands: Commands) {
+    commands.remove_resource::<Conflict>();
+}
+
+/// Escape and P pause the game, so they can't be bound
+pub const RESERVED: [KeyCode; 2] = [KeyCode::Escape, KeyCode::KeyP];
+
+/// Binds `control` unless it's used somewhere else, in which case the player is asked first
+fn bind(
+    commands: &mut Commands,
+    scheme: &mut ControlScheme,
+    which: PlayerAction,
+    slot: Slot,
+    control: Control,
+) {
+    let Some((other, other_slot)) = scheme.find(&control, (which, slot)) else {
+        scheme.get_mut(which).set(slot, Some(control));
+        return;
+    };
+    if other == which {
+        // Just moving it between the action's own keys
+        scheme.swap(which, slot, control, other, other_slot);
+        return;
+    }
+
+    let message = format!("{} is already {}", control.name(), other.str());
+    commands.insert_resource(Conflict {
+        which,
+        slot,
+        control,
+        other,
+        other_slot,
+    });
+    commands.spawn((
+        Name::new("Conflict Prompt"),
+        ConflictPrompt,
+        // Like `widget::ui_root`, but it blocks clicks on the menu underneath
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            flex_direction: FlexDirection::Column,
+            row_gap: px(20),
+            ..default()
+        },
+        GlobalZIndex(3),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+        DespawnOnExit(Menu::Controls),
+        children![
+            widget::header(message),
+            widget::label("Swap them?"),
+            widget::button("Swap", swap),
+            widget::button("Cancel", cancel_on_click),
+        ],
+    ));
+}
+
 fn execute_change_key(
+    mut commands: Commands,
     mut key: MessageReader<KeyboardInput>,
     mut buttons: Query<&mut KeyChange>,
     mut control_scheme: ResMut<ControlScheme>,
+    mut notice: Query<&mut Text, With<Notice>>,
 ) {
     for key in key.read() {
         if matches!(key.state, ButtonState::Released) {
             continue;
         }
         for mut button in &mut buttons {
-            if !button.changing {
+            if !button.changing || button.slot == Slot::Gamepad {
                 continue;
             }
             button.changing = false;
-            let code = control_scheme.get_mut(button.which);
-
-            code.0 = key.key_code;
-            code.1 = match &key.logical_key {
-                bevy::input::keyboard::Key::Character(str) => str.to_uppercase(),
-                keyboard::Key::ArrowDown => "Down".into(),
-                keyboard::Key::ArrowUp => "Up".into(),
-                keyboard::Key::ArrowLeft => "Left".into(),
-                keyboard::Key::ArrowRight => "Right".into(),
-                _ => format!("{:?}", key.logical_key),
+
+            let notice_text = if RESERVED.contains(&key.key_code) {
+                control_scheme.set_changed();
+                "Escape and P are kept for pausing"
+            } else {
+                let control = Control::Key(BoundKey {
+                    code: key.key_code,
+                    name: key_name(key),
+                });
+                bind(
+                    &mut commands,
+                    &mut control_scheme,
+                    button.which,
+                    button.slot,
+                    control,
+                );
+                ""
+            };
+            for mut text in &mut notice {
+                text.0 = notice_text.into();
             }
         }
     }
 }
 
+/// What the key says on the player's keyboard layout
+fn key_name(key: &KeyboardInput) -> String {
+    match &key.logical_key {
+        bevy::input::keyboard::Key::Character(str) => str.to_uppercase(),
+        keyboard::Key::ArrowDown => "Down".into(),
+        keyboard::Key::ArrowUp => "Up".into(),
+        keyboard::Key::ArrowLeft => "Left".into(),
+        keyboard::Key::ArrowRight => "Right".into(),
+        _ => format!("{:?}", key.logical_key),
+    }
+}
+
+fn execute_change_button(
+    mut commands: Commands,
+    mut pressed: MessageReader<GamepadButtonStateChangedEvent>,
+    mut buttons: Query<&mut KeyChange>,
+    mut control_scheme: ResMut<ControlScheme>,
+) {
+    for pressed in pressed.read() {
+        if matches!(pressed.state, ButtonState::Released) {
+            continue;
+        }
+        for mut button in &mut buttons {
+            if !button.changing || button.slot != Slot::Gamepad {
+                continue;
+            }
+            button.changing = false;
+            bind(
+                &mut commands,
+                &mut control_scheme,
+                button.which,
+                button.slot,
+                Control::Button(pressed.button),
+            );
+        }
+    }
+}
+
+fn reset(
+    _: On<Pointer<Click>>,
+    mut scheme: ResMut<ControlScheme>,
+    mut notice: Query<&mut Text, With<Notice>>,
+) {
+    *scheme = ControlScheme::default();
+    for mut text in &mut notice {
+        text.0 = "Back to the default controls".into();
+    }
+}
+
+/// What's printed on the button, on an Xbox-style controller
+fn button_name(button: Option<GamepadButton>) -> String {
+    let Some(button) = button else {
+        return "-".into();
+    };
+    match button {
+        GamepadButton::South => "A".into(),
+        GamepadButton::East => "B".into(),
+        GamepadButton::North => "Y".into(),
+        GamepadButton::West => "X".into(),
+        GamepadButton::LeftTrigger => "LB".into(),
+        GamepadButton::LeftTrigger2 => "LT".into(),
+        GamepadButton::RightTrigger => "RB".into(),
+        GamepadButton::RightTrigger2 => "RT".into(),
+        GamepadButton::LeftThumb => "LS".into(),
+        GamepadButton::RightThumb => "RS".into(),
+        GamepadButton::DPadUp => "D-pad up".into(),
+        GamepadButton::DPadDown => "D-pad down".into(),
+        GamepadButton::DPadLeft => "D-pad left".into(),
+        GamepadButton::DPadRight => "D-pad right".into(),
+        GamepadButton::Other(n) => format!("#{n}"),
+        other => format!("{other:?}"),
+    }
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct DeadzoneLabel;
+
+fn deadzone_label() -> impl Bundle {
+    (
+        widget::label(""),
+        DeadzoneLabel,
+        Node {
+            justify_self: JustifySelf::End,
+            ..default()
+        },
+    )
+}
+
+const DEADZONE_STEP: f32 = 0.05;
+const DEADZONE_MAX: f32 = 0.5;
+
+fn lower_deadzone(_: On<Pointer<Click>>, mut scheme: ResMut<ControlScheme>) {
+    scheme.deadzone = (scheme.deadzone - DEADZONE_STEP).max(0.);
+}
+
+fn raise_deadzone(_: On<Pointer<Click>>, mut scheme: ResMut<ControlScheme>) {
+    scheme.deadzone = (scheme.deadzone + DEADZONE_STEP).min(DEADZONE_MAX);
+}
+
+/// A key, and what it says on the player's keyboard layout
+#[derive(Reflect, Debug, Clone, PartialEq, Eq)]
+pub struct BoundKey {
+    pub code: KeyCode,
+    pub name: String,
+}
+
+impl BoundKey {
+    fn new(code: KeyCode, name: &str) -> Self {
+        Self {
+            code,
+            name: name.into(),
+        }
+    }
+}
+
+/// The keys and gamepad button for one action
+#[derive(Reflect, Debug, Clone)]
+pub struct Binding {
+    pub primary: Option<BoundKey>,
+    pub secondary: Option<BoundKey>,
+    pub button: Option<GamepadButton>,
+}
+
+impl Binding {
+    const fn new(primary: BoundKey, secondary: Option<BoundKey>, button: GamepadButton) -> Self {
+        Self {
+            primary: Some(primary),
+            secondary,
+            button: Some(button),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &BoundKey> {
+        self.primary.iter().chain(&self.secondary)
+    }
+
+    fn slot(&self, slot: Slot) -> Option<Control> {
+        match slot {
+            Slot::Primary => self.primary.clone().map(Control::Key),
+            Slot::Secondary => self.secondary.clone().map(Control::Key),
+            Slot::Gamepad => self.button.map(Control::Button),
+        }
+    }
+
+    /// A key in the gamepad slot, or the other way around, clears it
+    fn set(&mut self, slot: Slot, control: Option<Control>) {
+        match slot {
+            Slot::Primary => self.primary = control.and_then(Control::key),
+            Slot::Secondary => self.secondary = control.and_then(Control::key),
+            Slot::Gamepad => self.button = control.and_then(Control::button),
+        }
+    }
+}
+
+/// Anything that can be bound to an action
+#[derive(Reflect, Debug, Clone, PartialEq, Eq)]
+enum Control {
+    Key(BoundKey),
+    Button(GamepadButton),
+}
+
+impl Control {
+    fn key(self) -> Option<BoundKey> {
+        match self {
+            Self::Key(key) => Some(key),
+            Self::Button(_) => None,
+        }
+    }
+
+    fn button(self) -> Option<GamepadButton> {
+        match self {
+            Self::Key(_) => None,
+            Self::Button(button) => Some(button),
+        }
+    }
+
+    /// Keys with different names are still the same key
+    fn same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Key(a), Self::Key(b)) => a.code == b.code,
+            (Self::Button(a), Self::Button(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::Key(key) => key.name.clone(),
+            Self::Button(button) => button_name(Some(*button)),
+        }
+    }
+}
+
 #[derive(Resource, Reflect, Debug)]
 #[reflect(Resource)]
 pub struct ControlScheme {
-    up: (KeyCode, String),
-    down: (KeyCode, String),
-    left: (KeyCode, String),
-    right: (KeyCode, String),
-    bleat: (KeyCode, String),
+    up: Binding,
+    down: Binding,
+    left: Binding,
+    right: Binding,
+    bleat: Binding,
+    jump: Binding,
+    assist: Binding,
+    boost: Binding,
+    /// The left stick is ignored until it's pushed this far, from 0 to 1
+    pub deadzone: f32,
 }
 
 impl Default for ControlScheme {
     fn default() -> Self {
+        let key = BoundKey::new;
         Self {
-            up: (KeyCode::KeyW, "W".into()),
-            down: (KeyCode::KeyS, "S".into()),
-            left: (KeyCode::KeyA, "A".into()),
-            right: (KeyCode::KeyD, "D".into()),
-            bleat: (KeyCode::Space, "Space".into()),
+            up: Binding::new(
+                key(KeyCode::KeyW, "W"),
+                Some(key(KeyCode::ArrowUp, "Up")),
+                GamepadButton::DPadUp,
+            ),
+            down: Binding::new(
+                key(KeyCode::KeyS, "S"),
+                Some(key(KeyCode::ArrowDown, "Down")),
+                GamepadButton::DPadDown,
+            ),
+            left: Binding::new(
+                key(KeyCode::KeyA, "A"),
+                Some(key(KeyCode::ArrowLeft, "Left")),
+                GamepadButton::DPadLeft,
+            ),
+            right: Binding::new(
+                key(KeyCode::KeyD, "D"),
+                Some(key(KeyCode::ArrowRight, "Right")),
+                GamepadButton::DPadRight,
+            ),
+            bleat: Binding::new(key(KeyCode::Space, "Space"), None, GamepadButton::South),
+            jump: Binding::new(key(KeyCode::KeyE, "E"), None, GamepadButton::West),
+            assist: Binding::new(key(KeyCode::KeyQ, "Q"), None, GamepadButton::North),
+            boost: Binding::new(
+                key(KeyCode::ShiftLeft, "Shift"),
+                None,
+                GamepadButton::RightTrigger,
+            ),
+            deadzone: 0.15,
         }
     }
 }
 
-fn update_labels(scheme: Res<ControlScheme>, label: Query<(&mut Text, &KeyLabel)>) {
+fn update_labels(
+    scheme: Res<ControlScheme>,
+    label: Query<(&mut Text, &KeyLabel)>,
+    deadzone: Query<&mut Text, (With<DeadzoneLabel>, Without<KeyLabel>)>,
+) {
     for (mut text, label) in label {
-        text.0.clone_from(&scheme.get(label.which).1);
+        let binding = scheme.get(label.which);
+        text.0 = match label.slot {
+            Slot::Primary => key_label(binding.primary.as_ref()),
+            Slot::Secondary => key_label(binding.secondary.as_ref()),
+            Slot::Gamepad => button_name(binding.button),
+        };
     }
+    for mut text in deadzone {
+        text.0 = format!("Stick deadzone {:.0}%", 100. * scheme.deadzone);
+    }
+}
+
+fn key_label(key: Option<&BoundKey>) -> String {
+    key.map_or_else(|| "-".into(), |key| key.name.clone())
 }
 
+pub const ACTIONS: [PlayerAction; 8] = [Up, Down, Left, Right, Bleat, Jump, Assist, Boost];
+
 impl ControlScheme {
-    const fn get(&self, key: PlayerAction) -> &(KeyCode, String) {
+    pub const fn get(&self, key: PlayerAction) -> &Binding {
         match key {
             Up => &self.up,
             Down => &self.down,
             Left => &self.left,
             Right => &self.right,
             Bleat => &self.bleat,
+            Jump => &self.jump,
+            Assist => &self.assist,
+            Boost => &self.boost,
         }
     }
 
-    const fn get_mut(&mut self, key: PlayerAction) -> &mut (KeyCode, String) {
+    pub const fn get_mut(&mut self, key: PlayerAction) -> &mut Binding {
         match key {
             Up => &mut self.up,
             Down => &mut self.down,
             Left => &mut self.left,
             Right => &mut self.right,
             Bleat => &mut self.bleat,
+            Jump => &mut self.jump,
+            Assist => &mut self.assist,
+            Boost => &mut self.boost,
         }
     }
 
     pub fn by_keycode(&self, code: KeyCode) -> impl Iterator<Item = PlayerAction> {
-        [Up, Down, Left, Right, Bleat]
+        ACTIONS
+            .into_iter()
+            .filter(move |k| self.get(*k).keys().any(|key| key.code == code))
+    }
+
+    pub fn by_button(&self, button: GamepadButton) -> impl Iterator<Item = PlayerAction> {
+        ACTIONS
             .into_iter()
-            .filter(move |k| self.get(*k).0 == code)
+            .filter(move |k| self.get(*k).button == Some(button))
+    }
+
+    /// Where else `control` is bound, other than at `skip`
+    fn find(&self, control: &Control, skip: (PlayerAction, Slot)) -> Option<(PlayerAction, Slot)> {
+        ACTIONS
+            .into_iter()
+            .flat_map(|action| SLOTS.map(|slot| (action, slot)))
+            .filter(|place| *place != skip)
+            .find(|(action, slot)| {
+                self.get(*action)
+                    .slot(*slot)
+                    .is_some_and(|bound| bound.same(control))
+            })
+    }
+
+    /// Binds `control`, and gives whatever was there before to where `control` was
+    fn swap(
+        &mut self,
+        which: PlayerAction,
+        slot: Slot,
+        control: Control,
+        other: PlayerAction,
+        other_slot: Slot,
+    ) {
+        let old = self.get(which).slot(slot);
+        self.get_mut(other).set(other_slot, old);
+        self.get_mut(which).set(slot, Some(control));
+    }
+
+    /// Unbinds reserved keys, and anything bound twice after its first use. Primary keys
+    /// go first, so they win over secondary ones.
+    pub fn remove_conflicts(&mut self) {
+        let mut seen: Vec<Control> = Vec::new();
+        for slot in SLOTS {
+            for action in ACTIONS {
+                let Some(control) = self.get(action).slot(slot) else {
+                    continue;
+                };
+                let reserved = control
+                    .clone()
+                    .key()
+                    .is_some_and(|key| RESERVED.contains(&key.code));
+                if reserved || seen.iter().any(|other| other.same(&control)) {
+                    warn!(
+                        ?action,
+                        ?slot,
+                        "Unbinding {}, it's used elsewhere",
+                        control.name()
+                    );
+                    self.get_mut(action).set(slot, None);
+                } else {
+                    seen.push(control);
+                }
+            }
+        }
     }
 }
 
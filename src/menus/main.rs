@@ -23,6 +23,7 @@ fn spawn_main_menu(mut commands: Commands) {
             widget::button("Play", enter_loading_or_gameplay_screen),
             widget::button("Skip tutorial", skip_tutorial),
             widget::button("Controls", open_controls_menu),
+            widget::button("Settings", open_settings_menu),
             widget::button("Credits", open_credits_menu),
             widget::button("Exit", exit_app),
         ],
@@ -31,6 +32,7 @@ fn spawn_main_menu(mut commands: Commands) {
             widget::button("Play", enter_loading_or_gameplay_screen),
             widget::button("Skip tutorial", skip_tutorial),
             widget::button("Controls", open_controls_menu),
+            widget::button("Settings", open_settings_menu),
             widget::button("Credits", open_credits_menu),
         ],
     ));
@@ -81,6 +83,10 @@ fn open_controls_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Men
     next_menu.set(Menu::Controls);
 }
 
+fn open_settings_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
+
 fn open_credits_menu(_: On<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Credits);
 }
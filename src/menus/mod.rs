@@ -4,8 +4,9 @@ mod controls;
 mod credits;
 mod main;
 mod pause;
+mod settings;
 
-pub use controls::{ControlScheme, PlayerAction};
+pub use controls::{ACTIONS, BoundKey, ControlScheme, PlayerAction};
 pub use main::start_already;
 
 use bevy::prelude::*;
@@ -18,6 +19,7 @@ pub fn plugin(app: &mut App) {
         main::plugin,
         pause::plugin,
         controls::plugin,
+        settings::plugin,
     ));
 }
 
@@ -28,5 +30,6 @@ pub enum Menu {
     Main,
     Credits,
     Controls,
+    Settings,
     Pause,
 }
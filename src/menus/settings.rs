@@ -0,0 +1,191 @@
+//! The settings menu, for sound and accessibility.
+//!
+//! Everything here is saved between runs, see [`crate::settings`].
+
+use bevy::{ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    demo::sheep::assist::AssistMode,
+    menus::{
+        Menu,
+        controls::{go_back, go_back_on_click},
+    },
+    settings::{MAX_WARNING_SECS, Settings},
+    theme::prelude::*,
+};
+
+use Setting::{
+    Assist, BleatRings, GlobalVolume, JumpWarning, MusicVolume, SfxVolume, ShowDestination,
+    VirtualJoystick, WarningSound,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Settings), (spawn_menu, update_labels).chain());
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Settings).and(input_just_pressed(KeyCode::Escape))),
+    );
+    app.add_systems(
+        Update,
+        update_labels
+            .run_if(in_state(Menu::Settings))
+            .run_if(resource_changed::<Settings>),
+    );
+}
+
+fn spawn_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Settings"),
+        GlobalZIndex(2),
+        DespawnOnExit(Menu::Settings),
+        children![
+            widget::header("Settings"),
+            (
+                Name::new("Settings List"),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(10),
+                    ..default()
+                },
+                Children::spawn(SpawnIter(SETTINGS.into_iter().map(Setting::row))),
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+#[derive(Clone, Copy, Reflect, Debug, PartialEq, Eq)]
+enum Setting {
+    GlobalVolume,
+    MusicVolume,
+    SfxVolume,
+    JumpWarning,
+    WarningSound,
+    ShowDestination,
+    Assist,
+    VirtualJoystick,
+    BleatRings,
+}
+
+const SETTINGS: [Setting; 9] = [
+    GlobalVolume,
+    MusicVolume,
+    SfxVolume,
+    JumpWarning,
+    WarningSound,
+    ShowDestination,
+    Assist,
+    VirtualJoystick,
+    BleatRings,
+];
+
+const VOLUME_STEP: f32 = 0.1;
+const WARNING_STEP: f32 = 0.25;
+
+impl Setting {
+    fn row(self) -> impl Bundle {
+        let (lower, raise) = self.buttons();
+        (
+            Name::new(format!("{self:?} Row")),
+            Node {
+                align_items: AlignItems::Center,
+                column_gap: px(30),
+                ..default()
+            },
+            children![
+                (
+                    widget::label(""),
+                    SettingLabel(self),
+                    Node {
+                        width: px(340),
+                        ..default()
+                    },
+                ),
+                widget::narrow_button_with_bundle(
+                    lower,
+                    move |_: On<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                        self.change(&mut settings, false);
+                    },
+                    (),
+                    ()
+                ),
+                widget::narrow_button_with_bundle(
+                    raise,
+                    move |_: On<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                        self.change(&mut settings, true);
+                    },
+                    (),
+                    ()
+                ),
+            ],
+        )
+    }
+
+    /// What the two buttons next to the setting say
+    const fn buttons(self) -> (&'static str, &'static str) {
+        match self {
+            GlobalVolume | MusicVolume | SfxVolume | JumpWarning => ("-", "+"),
+            WarningSound | ShowDestination | VirtualJoystick | BleatRings => ("Off", "On"),
+            Assist => ("<", ">"),
+        }
+    }
+
+    fn describe(self, settings: &Settings) -> String {
+        let percent = |volume: f32| format!("{:.0}%", 100. * volume);
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        match self {
+            GlobalVolume => format!("Volume {}", percent(settings.global_volume)),
+            MusicVolume => format!("Music {}", percent(settings.music_volume)),
+            SfxVolume => format!("Sounds {}", percent(settings.sfx_volume)),
+            JumpWarning if settings.jump_warning_secs <= 0. => "Jump warning off".into(),
+            JumpWarning => format!("Jump warning {:.2}s", settings.jump_warning_secs),
+            WarningSound => format!("Warning sound {}", on_off(settings.warning_sound)),
+            ShowDestination => format!("Show next sheep {}", on_off(settings.show_destination)),
+            Assist => match settings.assist {
+                AssistMode::AlwaysOn => "Find me: always".into(),
+                AssistMode::OnDemand => "Find me: on a key".into(),
+                AssistMode::Off => "Find me: off".into(),
+            },
+            VirtualJoystick => format!("Touch joystick {}", on_off(settings.virtual_joystick)),
+            BleatRings => format!("Bleat rings {}", on_off(settings.bleat_rings)),
+        }
+    }
+
+    fn change(self, settings: &mut Settings, raise: bool) {
+        let step = |value: f32, step: f32, max: f32| {
+            let value = if raise { value + step } else { value - step };
+            // Don't pile up rounding errors
+            ((value / step).round() * step).clamp(0., max)
+        };
+        match self {
+            GlobalVolume => settings.global_volume = step(settings.global_volume, VOLUME_STEP, 1.),
+            MusicVolume => settings.music_volume = step(settings.music_volume, VOLUME_STEP, 1.),
+            SfxVolume => settings.sfx_volume = step(settings.sfx_volume, VOLUME_STEP, 1.),
+            JumpWarning => {
+                settings.jump_warning_secs =
+                    step(settings.jump_warning_secs, WARNING_STEP, MAX_WARNING_SECS);
+            }
+            WarningSound => settings.warning_sound = raise,
+            ShowDestination => settings.show_destination = raise,
+            VirtualJoystick => settings.virtual_joystick = raise,
+            BleatRings => settings.bleat_rings = raise,
+            Assist => {
+                settings.assist = match (settings.assist, raise) {
+                    (AssistMode::AlwaysOn, true) | (AssistMode::Off, false) => AssistMode::OnDemand,
+                    (AssistMode::OnDemand, true) | (AssistMode::AlwaysOn, false) => AssistMode::Off,
+                    (AssistMode::Off, true) | (AssistMode::OnDemand, false) => AssistMode::AlwaysOn,
+                };
+            }
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct SettingLabel(Setting);
+
+fn update_labels(settings: Res<Settings>, labels: Query<(&mut Text, &SettingLabel)>) {
+    for (mut text, label) in labels {
+        text.0 = label.0.describe(&settings);
+    }
+}
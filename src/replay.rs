@@ -0,0 +1,281 @@
+//! Record the player's input for a run, and play it back.
+//!
+//! Run the game with `--record <file>` to log the seed and the [`PlayerInput`] of every fixed tick
+//! of a run, or with `--replay <file>` to feed such a log back instead of the keyboard.
+
+use std::fs;
+
+use bevy::prelude::*;
+
+use crate::{
+    Pause,
+    controls::PlayerInput,
+    menus::PlayerAction::{self, Assist, Bleat, Boost, Down, Jump, Left, Right, Up},
+    rng::GameRng,
+    screens::Screen,
+};
+
+pub fn plugin(app: &mut App) {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(path) = arg_value(&args, "--record") {
+        app.insert_resource(Recorder {
+            path,
+            ticks: Vec::new(),
+        });
+    }
+    if let Some(path) = arg_value(&args, "--replay") {
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Replay::parse(&text))
+        {
+            Ok(replay) => {
+                app.insert_resource(replay);
+            }
+            Err(error) => error!(path, error, "Couldn't load replay"),
+        }
+    }
+
+    app.add_systems(Startup, use_replay_seed.run_if(resource_exists::<Replay>));
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        clear_recording.run_if(resource_exists::<Recorder>),
+    );
+    app.add_systems(
+        FixedPreUpdate,
+        (
+            replay.run_if(resource_exists::<Replay>),
+            record.run_if(resource_exists::<Recorder>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
+    );
+    app.add_systems(
+        OnExit(Screen::Gameplay),
+        (
+            save_recording.run_if(resource_exists::<Recorder>),
+            stop_replay.run_if(resource_exists::<Replay>),
+        ),
+    );
+}
+
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    let position = args.iter().position(|arg| arg == flag)?;
+    args.get(position + 1).cloned()
+}
+
+const HEADER: &str = "which-sheep replay v1";
+/// New actions go at the end, so older replays still work
+const ACTIONS: [PlayerAction; 8] = [Up, Down, Left, Right, Bleat, Jump, Assist, Boost];
+
+/// One fixed tick of input
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Tick {
+    bits: u8,
+    stick: Vec2,
+}
+
+fn to_tick(input: &PlayerInput) -> Tick {
+    let bits = ACTIONS
+        .iter()
+        .enumerate()
+        .filter(|(_, action)| input.get(**action))
+        .map(|(i, _)| 1 << i)
+        .sum();
+    Tick {
+        bits,
+        stick: input.stick,
+    }
+}
+
+fn from_tick(tick: Tick, input: &mut PlayerInput) {
+    for (i, action) in ACTIONS.into_iter().enumerate() {
+        *input.get_mut(action) = tick.bits & (1 << i) != 0;
+    }
+    input.stick = tick.stick;
+}
+
+/// Present while recording. The log is written when the run ends.
+#[derive(Resource, Debug)]
+pub struct Recorder {
+    path: String,
+    ticks: Vec<Tick>,
+}
+
+fn clear_recording(mut recorder: ResMut<Recorder>) {
+    recorder.ticks.clear();
+}
+
+fn record(input: Res<PlayerInput>, mut recorder: ResMut<Recorder>) {
+    recorder.ticks.push(to_tick(&input));
+}
+
+fn save_recording(recorder: Res<Recorder>, rng: Res<GameRng>) {
+    match fs::write(&recorder.path, to_text(rng.seed(), &recorder.ticks)) {
+        Ok(()) => info!(path = recorder.path, "Saved recording"),
+        Err(error) => error!(path = recorder.path, %error, "Couldn't save recording"),
+    }
+}
+
+fn to_text(seed: u64, ticks: &[Tick]) -> String {
+    let mut lines = vec![HEADER.to_string(), format!("seed {seed}")];
+
+    // Store runs of identical ticks as `<bits> <count>`, plus `<x> <y>` if the stick is used
+    let mut ticks = ticks.iter().peekable();
+    while let Some(&tick) = ticks.next() {
+        let mut count = 1;
+        while ticks.next_if_eq(&&tick).is_some() {
+            count += 1;
+        }
+        let Tick { bits, stick } = tick;
+        if stick == Vec2::ZERO {
+            lines.push(format!("{bits} {count}"));
+        } else {
+            lines.push(format!("{bits} {count} {} {}", stick.x, stick.y));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Present while replaying. [`crate::controls`] stops reading the keyboard meanwhile.
+#[derive(Resource, Debug)]
+pub struct Replay {
+    seed: u64,
+    ticks: Vec<Tick>,
+    next: usize,
+}
+
+impl Replay {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err("Not a replay file".into());
+        }
+        let seed = lines
+            .next()
+            .and_then(|line| line.strip_prefix("seed "))
+            .and_then(|seed| seed.parse().ok())
+            .ok_or("Missing seed")?;
+
+        let mut ticks = Vec::new();
+        for line in lines {
+            let (tick, count) = parse_line(line).ok_or_else(|| format!("Bad line {line:?}"))?;
+            ticks.extend(std::iter::repeat_n(tick, count));
+        }
+
+        Ok(Self {
+            seed,
+            ticks,
+            next: 0,
+        })
+    }
+}
+
+/// Older replays don't have the stick
+fn parse_line(line: &str) -> Option<(Tick, usize)> {
+    let mut words = line.split(' ');
+    let bits = words.next()?.parse().ok()?;
+    let count = words.next()?.parse().ok()?;
+    let stick = match (words.next(), words.next()) {
+        (None, None) => Vec2::ZERO,
+        (Some(x), Some(y)) => Vec2::new(x.parse().ok()?, y.parse().ok()?),
+        _ => return None,
+    };
+    if words.next().is_some() {
+        return None;
+    }
+    Some((Tick { bits, stick }, count))
+}
+
+fn use_replay_seed(replay: Res<Replay>, mut rng: ResMut<GameRng>) {
+    rng.next_seed = Some(replay.seed);
+}
+
+fn replay(mut commands: Commands, mut replay: ResMut<Replay>, mut input: ResMut<PlayerInput>) {
+    let Some(&tick) = replay.ticks.get(replay.next) else {
+        info!("Replay finished");
+        *input = PlayerInput::default();
+        commands.remove_resource::<Replay>();
+        return;
+    };
+    from_tick(tick, &mut input);
+    replay.next += 1;
+}
+
+fn stop_replay(mut commands: Commands) {
+    commands.remove_resource::<Replay>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        demo::{sheep::Sheep, wolf::Wolf},
+        headless,
+    };
+
+    /// Six seconds, the same run the gameplay tests survive
+    const TICKS: usize = 384;
+
+    fn start_run(app: &mut App) {
+        app.add_plugins(plugin);
+        // Runs `Startup`, which picks up a replay's seed
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Gameplay);
+        // Only starts the run, the first update doesn't tick
+        app.update();
+    }
+
+    fn positions(app: &mut App) -> Vec<Vec2> {
+        let mut query = app
+            .world_mut()
+            .query_filtered::<&Transform, Or<(With<Sheep>, With<Wolf>)>>();
+        let mut positions: Vec<Vec2> = query
+            .iter(app.world())
+            .map(|transf| transf.translation.truncate())
+            .collect();
+        positions.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        positions
+    }
+
+    #[test]
+    fn replay_plays_the_recorded_run() {
+        let mut recording = headless::app();
+        recording.world_mut().resource_mut::<GameRng>().next_seed = Some(42);
+        recording.insert_resource(Recorder {
+            path: String::new(),
+            ticks: Vec::new(),
+        });
+        start_run(&mut recording);
+        for tick in 0..TICKS {
+            let mut input = recording.world_mut().resource_mut::<PlayerInput>();
+            input.up = tick % 120 < 60;
+            input.left = tick % 200 < 50;
+            input.bleat = tick % 150 == 0;
+            input.stick = if tick % 300 > 250 {
+                Vec2::new(0.5, -0.5)
+            } else {
+                Vec2::ZERO
+            };
+            recording.update();
+        }
+        let recorder = recording.world().resource::<Recorder>();
+        let text = to_text(
+            recording.world().resource::<GameRng>().seed(),
+            &recorder.ticks,
+        );
+
+        let mut replaying = headless::app();
+        replaying.insert_resource(Replay::parse(&text).unwrap());
+        start_run(&mut replaying);
+        for _ in 0..TICKS {
+            replaying.update();
+        }
+
+        let played = positions(&mut recording);
+        assert!(!played.is_empty());
+        assert_eq!(positions(&mut replaying), played);
+    }
+}
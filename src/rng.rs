@@ -0,0 +1,60 @@
+//! The one source of randomness for gameplay, so a run can be reproduced from its seed.
+
+use bevy::prelude::*;
+use rand::{Rng, RngCore, SeedableRng, rngs::StdRng};
+
+use crate::{demo::level::spawn_level, screens::Screen};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<GameRng>();
+    app.add_systems(OnEnter(Screen::Gameplay), reseed.before(spawn_level));
+}
+
+#[derive(Resource, Debug)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+    /// When set, the next run uses this seed instead of a fresh one.
+    pub next_seed: Option<u64>,
+}
+
+impl GameRng {
+    fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            next_seed: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(rand::rng().random())
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.rng.fill_bytes(dst);
+    }
+}
+
+fn reseed(mut rng: ResMut<GameRng>) {
+    let seed = rng.next_seed.take().unwrap_or_else(|| rand::rng().random());
+    *rng = GameRng::from_seed(seed);
+    info!(seed, "Starting run");
+}
@@ -2,19 +2,9 @@
 
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
-use crate::{
-    Pause,
-    demo::{level::spawn_level, player},
-    menus::Menu,
-    screens::Screen,
-};
+use crate::{Pause, menus::Menu, screens::Screen};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::Gameplay), spawn_level);
-    app.add_systems(OnEnter(Screen::Intro), spawn_level);
-    app.add_systems(OnEnter(Screen::Gameplay), player::choose.after(spawn_level));
-    app.add_systems(OnEnter(Screen::Intro), player::choose.after(spawn_level));
-
     // Toggle pause on key press.
     app.add_systems(
         Update,
@@ -0,0 +1,423 @@
+//! Keep the player's settings between runs.
+//!
+//! [`Settings`] and the [`ControlScheme`] are loaded when the game starts, and saved whenever they
+//! change. Natively they go in a text file in the user's config directory, on the web in
+//! `localStorage`. A file that can't be read falls back to the defaults, and a bad line only loses
+//! that one setting.
+
+use bevy::{
+    audio::Volume,
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, Enum, Typed, VariantInfo},
+};
+
+use crate::{
+    audio::mixer::Mixer,
+    controls::pointer::PointerControls,
+    demo::sheep::{
+        assist::{Assist, AssistMode},
+        bleat::BleatWaves,
+        warning::JumpWarning,
+    },
+    menus::{ACTIONS, BoundKey, ControlScheme},
+};
+
+pub fn plugin(app: &mut App) {
+    let (settings, scheme) = load();
+    app.insert_resource(settings);
+    // Replaces the default scheme from `controls::plugin`
+    app.insert_resource(scheme);
+
+    app.add_systems(
+        Update,
+        (
+            apply.run_if(resource_changed::<Settings>),
+            // Nothing to save right after loading
+            save.run_if(
+                resource_changed::<Settings>
+                    .or(resource_changed::<ControlScheme>)
+                    .and(not(resource_added::<Settings>)),
+            ),
+        ),
+    );
+}
+
+/// What the player chose in the settings menu
+#[derive(Resource, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Resource)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Settings {
+    /// From 0 to 1, like the other volumes
+    pub global_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    /// How long before a jump the player is warned, 0 turns the warning off
+    pub jump_warning_secs: f32,
+    pub warning_sound: bool,
+    /// Mark the sheep the player is about to jump to
+    pub show_destination: bool,
+    pub assist: AssistMode,
+    /// Show on-screen controls on touch screens
+    pub virtual_joystick: bool,
+    /// Draw bleats spreading through the flock
+    pub bleat_rings: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let warning = JumpWarning::default();
+        Self {
+            global_volume: 1.,
+            music_volume: 1.,
+            sfx_volume: 1.,
+            jump_warning_secs: warning.lead_secs,
+            warning_sound: warning.sound,
+            show_destination: warning.show_destination,
+            assist: AssistMode::default(),
+            virtual_joystick: PointerControls::default().virtual_joystick,
+            bleat_rings: BleatWaves::default().show,
+        }
+    }
+}
+
+/// The longest jump warning that can be chosen
+pub const MAX_WARNING_SECS: f32 = 3.;
+
+fn apply(
+    settings: Res<Settings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut mixer: ResMut<Mixer>,
+    mut warning: ResMut<JumpWarning>,
+    mut assist: ResMut<Assist>,
+    mut pointer: ResMut<PointerControls>,
+    mut waves: ResMut<BleatWaves>,
+) {
+    global_volume.volume = Volume::Linear(settings.global_volume);
+    mixer.music.volume = settings.music_volume;
+    mixer.sound_effects.volume = settings.sfx_volume;
+    warning.lead_secs = settings.jump_warning_secs;
+    warning.sound = settings.warning_sound;
+    warning.show_destination = settings.show_destination;
+    assist.mode = settings.assist;
+    pointer.virtual_joystick = settings.virtual_joystick;
+    waves.show = settings.bleat_rings;
+}
+
+fn load() -> (Settings, ControlScheme) {
+    let text = match read() {
+        Ok(Some(text)) => text,
+        Ok(None) => return default(),
+        Err(error) => {
+            warn!(error, "Couldn't read settings, using the defaults");
+            return default();
+        }
+    };
+    parse(&text).unwrap_or_else(|error| {
+        warn!(error, "Couldn't load settings, using the defaults");
+        default()
+    })
+}
+
+fn save(settings: Res<Settings>, scheme: Res<ControlScheme>) {
+    if let Err(error) = write(&to_text(&settings, &scheme)) {
+        error!(error, "Couldn't save settings");
+    }
+}
+
+const HEADER: &str = "which-sheep settings v";
+/// Bump this when a setting changes meaning, and convert older files in [`parse_line`]
+const VERSION: u32 = 2;
+
+fn to_text(settings: &Settings, scheme: &ControlScheme) -> String {
+    let mut lines = vec![
+        format!("{HEADER}{VERSION}"),
+        format!("global_volume {}", settings.global_volume),
+        format!("music_volume {}", settings.music_volume),
+        format!("sfx_volume {}", settings.sfx_volume),
+        format!("jump_warning_secs {}", settings.jump_warning_secs),
+        format!("warning_sound {}", settings.warning_sound),
+        format!("show_destination {}", settings.show_destination),
+        format!("assist {}", settings.assist.variant_name()),
+        format!("virtual_joystick {}", settings.virtual_joystick),
+        format!("bleat_rings {}", settings.bleat_rings),
+        format!("deadzone {}", scheme.deadzone),
+    ];
+    // `primary <action> <key> <name>`, the name goes last since it can have spaces. A `-` means
+    // nothing is bound.
+    let key = |key: Option<&BoundKey>| {
+        key.map_or_else(
+            || "-".into(),
+            |key| format!("{} {}", key.code.variant_name(), key.name),
+        )
+    };
+    for action in ACTIONS {
+        let binding = scheme.get(action);
+        let action = action.variant_name();
+        lines.push(format!(
+            "primary {action} {}",
+            key(binding.primary.as_ref())
+        ));
+        lines.push(format!(
+            "secondary {action} {}",
+            key(binding.secondary.as_ref())
+        ));
+        lines.push(format!(
+            "button {action} {}",
+            binding.button.as_ref().map_or("-", Enum::variant_name)
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
+fn parse(text: &str) -> Result<(Settings, ControlScheme), String> {
+    let mut lines = text.lines();
+    let version: u32 = lines
+        .next()
+        .and_then(|line| line.strip_prefix(HEADER))
+        .and_then(|version| version.parse().ok())
+        .ok_or("Not a settings file")?;
+    if version > VERSION {
+        return Err(format!("Settings from a newer version (v{version})"));
+    }
+
+    let mut settings = Settings::default();
+    let mut scheme = ControlScheme::default();
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        if let Err(error) = parse_line(version, line, &mut settings, &mut scheme) {
+            warn!(line, error, "Skipping setting");
+        }
+    }
+    scheme.remove_conflicts();
+    Ok((settings, scheme))
+}
+
+fn parse_line(
+    version: u32,
+    line: &str,
+    settings: &mut Settings,
+    scheme: &mut ControlScheme,
+) -> Result<(), String> {
+    let (key, value) = line.split_once(' ').ok_or("Missing value")?;
+    match key {
+        "global_volume" => settings.global_volume = number(value, 1.)?,
+        "music_volume" => settings.music_volume = number(value, 1.)?,
+        "sfx_volume" => settings.sfx_volume = number(value, 1.)?,
+        "jump_warning_secs" => settings.jump_warning_secs = number(value, MAX_WARNING_SECS)?,
+        "warning_sound" => settings.warning_sound = flag(value)?,
+        "show_destination" => settings.show_destination = flag(value)?,
+        "assist" => settings.assist = variant(value)?,
+        "virtual_joystick" => settings.virtual_joystick = flag(value)?,
+        "bleat_rings" => settings.bleat_rings = flag(value)?,
+        "deadzone" => scheme.deadzone = number(value, 1.)?,
+        "primary" | "secondary" | "button" => {
+            let (action, value) = value.split_once(' ').ok_or("Missing binding")?;
+            let binding = scheme.get_mut(variant(action)?);
+            match key {
+                "primary" => binding.primary = bound_key(value)?,
+                "secondary" => binding.secondary = bound_key(value)?,
+                _ => binding.button = button(value)?,
+            }
+        }
+        // Before secondary keys, `bind <action> <key> <button or -> <name>`
+        "bind" if version == 1 => {
+            let mut words = value.splitn(4, ' ');
+            let mut next = || words.next().ok_or("Incomplete binding");
+            let binding = scheme.get_mut(variant(next()?)?);
+            let code = variant(next()?)?;
+            let button = button(next()?)?;
+            let name = next()?;
+            binding.primary = Some(BoundKey {
+                code,
+                name: name.into(),
+            });
+            binding.button = button;
+        }
+        _ => return Err("Unknown setting".into()),
+    }
+    Ok(())
+}
+
+/// A number from 0 to `max`
+fn number(value: &str, max: f32) -> Result<f32, String> {
+    let number: f32 = value.parse().map_err(|_| "Not a number")?;
+    if !number.is_finite() {
+        return Err("Not a number".into());
+    }
+    Ok(number.clamp(0., max))
+}
+
+fn flag(value: &str) -> Result<bool, String> {
+    value.parse().map_err(|_| "Not true or false".into())
+}
+
+/// `<key> <name>`, or `-`
+fn bound_key(value: &str) -> Result<Option<BoundKey>, String> {
+    if value == "-" {
+        return Ok(None);
+    }
+    let (code, name) = value.split_once(' ').ok_or("Missing key name")?;
+    Ok(Some(BoundKey {
+        code: variant(code)?,
+        name: name.into(),
+    }))
+}
+
+/// A button, or `-`
+fn button(value: &str) -> Result<Option<GamepadButton>, String> {
+    if value == "-" {
+        return Ok(None);
+    }
+    variant(value).map(Some)
+}
+
+/// A variant without fields, by name
+fn variant<T: FromReflect + Typed>(name: &str) -> Result<T, String> {
+    let unknown = || format!("Unknown {name:?}");
+    // `from_reflect` panics on variants that don't exist
+    let info = T::type_info().as_enum().map_err(|_| unknown())?;
+    if !matches!(info.variant(name), Some(VariantInfo::Unit(_))) {
+        return Err(unknown());
+    }
+    T::from_reflect(&DynamicEnum::new(name.to_string(), DynamicVariant::Unit)).ok_or_else(unknown)
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn path() -> Option<std::path::PathBuf> {
+    use std::{env::var_os, path::PathBuf};
+
+    let config = var_os("XDG_CONFIG_HOME")
+        .or_else(|| var_os("APPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("which-sheep").join("settings.txt"))
+}
+
+/// `None` if nothing was saved yet
+#[cfg(not(target_family = "wasm"))]
+fn read() -> Result<Option<String>, String> {
+    let path = path().ok_or("No config directory")?;
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn write(text: &str) -> Result<(), String> {
+    let path = path().ok_or("No config directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[cfg(target_family = "wasm")]
+const STORAGE_KEY: &str = "which-sheep settings";
+
+#[cfg(target_family = "wasm")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .ok_or("No window")?
+        .local_storage()
+        .map_err(|e| format!("{e:?}"))?
+        .ok_or_else(|| "No localStorage".into())
+}
+
+/// `None` if nothing was saved yet
+#[cfg(target_family = "wasm")]
+fn read() -> Result<Option<String>, String> {
+    local_storage()?
+        .get_item(STORAGE_KEY)
+        .map_err(|e| format!("{e:?}"))
+}
+
+#[cfg(target_family = "wasm")]
+fn write(text: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(STORAGE_KEY, text)
+        .map_err(|e| format!("{e:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menus::PlayerAction;
+
+    #[test]
+    fn round_trip() {
+        let settings = Settings {
+            music_volume: 0.25,
+            jump_warning_secs: 2.,
+            assist: AssistMode::AlwaysOn,
+            bleat_rings: true,
+            ..default()
+        };
+        let mut scheme = ControlScheme::default();
+        scheme.deadzone = 0.3;
+        let jump = scheme.get_mut(PlayerAction::Jump);
+        jump.secondary = Some(BoundKey {
+            code: KeyCode::Enter,
+            name: "Enter key".into(),
+        });
+        jump.button = None;
+        scheme.get_mut(PlayerAction::Up).secondary = None;
+
+        let (loaded, loaded_scheme) = parse(&to_text(&settings, &scheme)).unwrap();
+        assert_eq!(loaded, settings);
+        assert_eq!(
+            to_text(&loaded, &loaded_scheme),
+            to_text(&settings, &scheme)
+        );
+    }
+
+    #[test]
+    fn bad_lines_are_skipped() {
+        let text = "which-sheep settings v1\n\
+            music_volume 0.5\n\
+            sfx_volume loud\n\
+            global_volume NaN\n\
+            jump_warning_secs 100\n\
+            assist Sometimes\n\
+            primary Jump NotAKey X\n\
+            button Jump -\n\
+            primary Jump KeyP P\n\
+            colour blue\n";
+        let (settings, scheme) = parse(text).unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                music_volume: 0.5,
+                jump_warning_secs: MAX_WARNING_SECS,
+                ..default()
+            }
+        );
+        let mut expected = ControlScheme::default();
+        expected.get_mut(PlayerAction::Jump).button = None;
+        // P is kept for pausing
+        expected.get_mut(PlayerAction::Jump).primary = None;
+        let text = |scheme| to_text(&Settings::default(), scheme);
+        assert_eq!(text(&scheme), text(&expected));
+    }
+
+    #[test]
+    fn old_bindings_are_converted() {
+        let text = "which-sheep settings v1\n\
+            bind Up KeyI DPadUp I\n\
+            bind Down KeyW - W\n";
+        let (_, scheme) = parse(text).unwrap();
+        let up = scheme.get(PlayerAction::Up);
+        assert_eq!(up.primary.as_ref().unwrap().code, KeyCode::KeyI);
+        assert_eq!(up.secondary.as_ref().unwrap().code, KeyCode::ArrowUp);
+        let down = scheme.get(PlayerAction::Down);
+        assert_eq!(down.primary.as_ref().unwrap().code, KeyCode::KeyW);
+        assert_eq!(down.button, None);
+    }
+
+    #[test]
+    fn unknown_files_are_rejected() {
+        assert!(parse("").is_err());
+        assert!(parse("which-sheep replay v1\nseed 3\n").is_err());
+        assert!(parse("which-sheep settings v99\nmusic_volume 0.5\n").is_err());
+    }
+}
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
-use crate::{asset_tracking::LoadResource, audio::sound_effect};
+use crate::{
+    asset_tracking::LoadResource,
+    audio::{mixer::SoundGroup, sound_effect},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(apply_interaction_palette_on_click);
@@ -92,7 +95,10 @@ fn play_sound_effect_on_click(
     interaction_assets: If<Res<InteractionAssets>>,
     mut commands: Commands,
 ) {
-    commands.spawn(sound_effect(interaction_assets.click.clone(), 1.));
+    commands.spawn((
+        sound_effect(interaction_assets.click.clone(), 1.),
+        SoundGroup::Interface,
+    ));
 }
 
 fn play_sound_effect_on_over(
@@ -100,5 +106,8 @@ fn play_sound_effect_on_over(
     interaction_assets: If<Res<InteractionAssets>>,
     mut commands: Commands,
 ) {
-    commands.spawn(sound_effect(interaction_assets.hover.clone(), 1.));
+    commands.spawn((
+        sound_effect(interaction_assets.hover.clone(), 1.),
+        SoundGroup::Interface,
+    ));
 }
@@ -116,6 +116,36 @@ where
     )
 }
 
+/// Like [`button_with_bundle`], but narrow enough for a few of them to fit side by side in a row
+pub fn narrow_button_with_bundle<E, B, M, I>(
+    text: impl Into<String>,
+    action: I,
+    inner_bundle: impl Bundle,
+    text_bundle: impl Bundle,
+) -> impl Bundle
+where
+    E: EntityEvent,
+    B: Bundle,
+    I: IntoObserverSystem<E, B, M>,
+{
+    button_base_with_bundle(
+        text,
+        action,
+        (
+            inner_bundle,
+            Node {
+                width: px(240),
+                height: px(56),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                border_radius: BorderRadius::MAX,
+                ..default()
+            },
+        ),
+        text_bundle,
+    )
+}
+
 /// A simple button with text and an action defined as an [`Observer`]. The button's layout is provided by `button_bundle`.
 fn button_base<E, B, M, I>(
     text: impl Into<String>,
@@ -0,0 +1,690 @@
+use std::time::Duration;
+
+use bevy::{
+    input::{
+        ButtonState,
+        gamepad::{Gamepad, GamepadAxis},
+        keyboard::{Key, KeyboardInput, NativeKey},
+    },
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use which_sheep::{
+    controls::PlayerInput,
+    demo::{
+        cabbage::{Cabbage, Score},
+        level::N_SHEEP,
+        movement::HumanMind,
+        player::Jumped,
+        sheep::{
+            self, Sheep, SheepMind,
+            assist::Assist,
+            bleat::{BleatWave, BleatWaves, GATHER_SPEED},
+            boids::Velocity,
+            boost::Boost,
+            ego::{JUMP_COST, JumpMeter, JumpTimer},
+        },
+        wolf::{
+            DifficultySecs, NearMiss, Wolf, WolfState,
+            detective::{Detective, Suspicion},
+            pack::Role,
+        },
+    },
+    headless,
+    rng::GameRng,
+    screens::Screen,
+};
+
+fn start_run() -> App {
+    let mut app = headless::app();
+    app.world_mut().resource_mut::<GameRng>().next_seed = Some(42);
+    app.world_mut()
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Gameplay);
+    app.update();
+    app.update();
+    app
+}
+
+fn screen(app: &App) -> Screen {
+    *app.world().resource::<State<Screen>>().get()
+}
+
+fn player_position(app: &mut App) -> Vec3 {
+    let mut player = app
+        .world_mut()
+        .query_filtered::<&Transform, With<HumanMind>>();
+    player.single(app.world()).unwrap().translation
+}
+
+fn count_sheep(app: &mut App) -> usize {
+    let mut sheep = app.world_mut().query_filtered::<(), With<Sheep>>();
+    sheep.iter(app.world()).count()
+}
+
+/// So that no other sheep gets in the way of the player
+fn remove_other_sheep(app: &mut App) {
+    let mut others = app
+        .world_mut()
+        .query_filtered::<Entity, (With<Sheep>, Without<HumanMind>)>();
+    let others: Vec<Entity> = others.iter(app.world()).collect();
+    for id in others {
+        app.world_mut().despawn(id);
+    }
+}
+
+#[test]
+fn run_starts_with_a_full_flock() {
+    let mut app = start_run();
+
+    assert_eq!(screen(&app), Screen::Gameplay);
+    assert_eq!(count_sheep(&mut app), N_SHEEP);
+    player_position(&mut app);
+}
+
+#[test]
+fn wolf_eating_the_player_ends_the_game() {
+    let mut app = start_run();
+    remove_other_sheep(&mut app);
+
+    let position = player_position(&mut app);
+    app.world_mut().spawn((
+        Transform::from_translation(position),
+        Wolf::default(),
+        Sprite::default(),
+    ));
+    app.update();
+    app.update();
+
+    assert_eq!(screen(&app), Screen::GameOver);
+}
+
+#[test]
+fn eating_cabbage_scores() {
+    let mut app = start_run();
+    remove_other_sheep(&mut app);
+    assert_eq!(app.world().resource::<Score>().0, 0);
+
+    let position = player_position(&mut app);
+    app.world_mut()
+        .spawn((Transform::from_translation(position), Cabbage));
+    app.update();
+
+    assert_eq!(app.world().resource::<Score>().0, 1);
+    assert_eq!(screen(&app), Screen::Gameplay);
+}
+
+#[test]
+fn eaten_sheep_are_replaced() {
+    let mut app = start_run();
+
+    let mut sheep = app
+        .world_mut()
+        .query_filtered::<Entity, (With<Sheep>, Without<HumanMind>)>();
+    let eaten: Vec<Entity> = sheep.iter(app.world()).take(10).collect();
+    for id in eaten {
+        app.world_mut().despawn(id);
+    }
+    assert_eq!(count_sheep(&mut app), N_SHEEP - 10);
+
+    for _ in 0..20 {
+        app.update();
+    }
+
+    assert_eq!(count_sheep(&mut app), N_SHEEP);
+}
+
+#[test]
+fn sheep_flee_from_wolves() {
+    let mut app = start_run();
+
+    let mut sheep = app
+        .world_mut()
+        .query_filtered::<(Entity, &Transform), (With<SheepMind>, Without<HumanMind>)>();
+    let (id, transf) = sheep.iter(app.world()).next().unwrap();
+    let wolf_position = transf.translation + Vec3::new(40., 0., 0.);
+    app.world_mut().spawn((
+        Transform::from_translation(wolf_position),
+        Wolf::default(),
+        Sprite::default(),
+    ));
+    app.update();
+
+    let mind = app.world().get::<SheepMind>(id).unwrap();
+    let sheep::State::Fleeing { goal, .. } = mind.state else {
+        panic!("Sheep isn't fleeing: {mind:?}");
+    };
+    assert!(goal.x < 0., "Sheep is fleeing towards the wolf: {goal}");
+}
+
+fn wolf_state(app: &mut App) -> (WolfState, Vec3) {
+    let mut wolf = app.world_mut().query::<(&Wolf, &Transform)>();
+    let (wolf, transf) = wolf.single(app.world()).unwrap();
+    (wolf.state(), transf.translation)
+}
+
+#[test]
+fn dodging_a_charge_makes_the_wolf_give_up() {
+    let mut app = start_run();
+    remove_other_sheep(&mut app);
+
+    let position = player_position(&mut app);
+    app.world_mut().spawn((
+        Transform::from_translation(position + Vec3::new(100., 0., 0.)),
+        Wolf::default(),
+        Sprite::default(),
+    ));
+    // Wait until the last moment the player can still get away
+    for _ in 0..200 {
+        let (state, wolf) = wolf_state(&mut app);
+        if state == WolfState::Charge && wolf.distance(player_position(&mut app)) < 50. {
+            break;
+        }
+        app.update();
+    }
+    assert_eq!(wolf_state(&mut app).0, WolfState::Charge);
+
+    // Sidestep, away from the side the wolf is coming in on
+    let wolf_above = wolf_state(&mut app).1.y > player_position(&mut app).y;
+    let mut input = app.world_mut().resource_mut::<PlayerInput>();
+    if wolf_above {
+        input.down = true;
+    } else {
+        input.up = true;
+    }
+    for _ in 0..30 {
+        if wolf_state(&mut app).0 == WolfState::GiveUp {
+            break;
+        }
+        app.update();
+    }
+
+    assert_eq!(wolf_state(&mut app).0, WolfState::GiveUp);
+    assert_eq!(screen(&app), Screen::Gameplay);
+    assert!(!app.world().resource::<Messages<NearMiss>>().is_empty());
+}
+
+#[test]
+fn roaming_wolves_wander_somewhere() {
+    let mut app = start_run();
+    remove_other_sheep(&mut app);
+    let position = player_position(&mut app);
+    app.world_mut().spawn((
+        Transform::from_translation(position + Vec3::new(250., 250., 0.)),
+        Wolf::default(),
+        Sprite::default(),
+    ));
+    app.update();
+
+    // Turning towards one place the whole time, not a new one every tick
+    let mut heading = app.world_mut().query_filtered::<&Velocity, With<Wolf>>();
+    let mut turns = Vec::new();
+    let mut last = heading.single(app.world()).unwrap().heading;
+    for _ in 0..25 {
+        app.update();
+        assert_eq!(wolf_state(&mut app).0, WolfState::Roam);
+        let now = heading.single(app.world()).unwrap().heading;
+        turns.push(last.angle_to(now));
+        last = now;
+    }
+    assert!(
+        turns.iter().all(|turn| *turn > -1e-3) || turns.iter().all(|turn| *turn < 1e-3),
+        "{turns:?}"
+    );
+}
+
+#[test]
+fn detectives_suspect_the_player() {
+    let mut app = start_run();
+    app.world_mut().spawn((
+        Transform::from_xyz(-300., 150., 0.),
+        Wolf::default(),
+        Detective,
+        Sprite::default(),
+    ));
+
+    let mut player = app.world_mut().query_filtered::<Entity, With<HumanMind>>();
+    let player = player.single(app.world()).unwrap();
+    // No sheep runs on and on like that
+    app.world_mut().resource_mut::<PlayerInput>().right = true;
+    for _ in 0..100 {
+        app.update();
+    }
+
+    let mut suspicion = app.world_mut().query::<(Entity, &Suspicion)>();
+    let (most_suspicious, _) = suspicion
+        .iter(app.world())
+        .max_by(|a, b| a.1.score.total_cmp(&b.1.score))
+        .unwrap();
+    assert_eq!(most_suspicious, player);
+}
+
+#[test]
+fn panicked_sheep_are_not_suspected() {
+    let mut app = start_run();
+    let mut sheep = app
+        .world_mut()
+        .query_filtered::<(Entity, &Transform), (With<SheepMind>, Without<HumanMind>)>();
+    let (id, transf) = sheep.iter(app.world()).next().unwrap();
+    let pos = transf.translation.xy();
+    let away = pos.normalize_or(Vec2::X);
+    // Watching, but too far to scare it
+    app.world_mut().spawn((
+        Transform::from_translation((pos - 300. * away).extend(0.)),
+        Wolf::default(),
+        Detective,
+        Sprite::default(),
+    ));
+
+    // Spooked again and again, without a wolf around
+    for _ in 0..200 {
+        let mut mind = app.world_mut().get_mut::<SheepMind>(id).unwrap();
+        if !mind.fleeing() {
+            mind.state = sheep::State::Fleeing {
+                goal: away,
+                speed: 200.,
+                panic: 0.5,
+                spread: true,
+            };
+        }
+        app.update();
+    }
+
+    let suspicion = app.world().get::<Suspicion>(id).unwrap();
+    assert!(suspicion.score < 0.02, "{}", suspicion.score);
+}
+
+#[test]
+fn wolves_hunt_as_a_pack() {
+    let mut app = start_run();
+
+    for corner in [Vec3::new(-300., 150., 0.), Vec3::new(300., -150., 0.)] {
+        app.world_mut().spawn((
+            Transform::from_translation(corner),
+            Wolf::default(),
+            Sprite::default(),
+        ));
+    }
+    // Long enough for them to get hungry
+    for _ in 0..34 {
+        app.update();
+    }
+
+    let mut wolves = app.world_mut().query::<&Wolf>();
+    let mut roles: Vec<Role> = wolves.iter(app.world()).map(|wolf| wolf.role).collect();
+    roles.sort_by_key(|role| *role == Role::Flanker);
+    assert_eq!(roles, [Role::Driver, Role::Flanker]);
+}
+
+#[test]
+fn jump_meter_switches_toward_the_direction() {
+    let mut app = start_run();
+    remove_other_sheep(&mut app);
+
+    let position = player_position(&mut app);
+    let target = app
+        .world_mut()
+        .spawn((
+            Sheep,
+            Transform::from_translation(position + Vec3::new(60., 5., 0.)),
+        ))
+        .id();
+    app.world_mut().resource_mut::<JumpMeter>().charge = JUMP_COST;
+    let mut input = app.world_mut().resource_mut::<PlayerInput>();
+    input.right = true;
+    input.jump = true;
+    app.update();
+    app.update();
+
+    assert!(app.world().get::<HumanMind>(target).is_some());
+    assert_eq!(app.world().resource::<JumpMeter>().charge, 0.);
+    assert!(!app.world().resource::<Messages<Jumped>>().is_empty());
+}
+
+#[test]
+fn jump_goes_to_the_warned_sheep() {
+    let mut app = start_run();
+    app.world_mut()
+        .resource_mut::<JumpTimer>()
+        .timer
+        .set_duration(Duration::from_secs(2));
+
+    let mut next = None;
+    for _ in 0..200 {
+        app.update();
+        next = app.world().resource::<JumpTimer>().next;
+        if next.is_some() {
+            break;
+        }
+    }
+    let next = next.expect("the warning never started");
+
+    for _ in 0..200 {
+        app.update();
+        if app.world().resource::<JumpTimer>().next.is_none() {
+            break;
+        }
+    }
+    assert!(app.world().get::<HumanMind>(next).is_some());
+}
+
+#[test]
+fn assist_shows_the_player_then_cools_down() {
+    let mut app = start_run();
+    let tint = |app: &mut App| {
+        let mut query = app.world_mut().query_filtered::<&Sprite, With<HumanMind>>();
+        query.single(app.world()).unwrap().color
+    };
+    let untinted = tint(&mut app);
+
+    app.world_mut().resource_mut::<PlayerInput>().assist = true;
+    app.update();
+    assert!(app.world().resource::<Assist>().showing());
+    assert_ne!(tint(&mut app), untinted);
+
+    // Let go and wait for it to fade, but not for the cooldown
+    app.world_mut().resource_mut::<PlayerInput>().assist = false;
+    let show_secs = app.world().resource::<Assist>().show_secs;
+    for _ in 0..(show_secs * 64.) as usize + 2 {
+        app.update();
+    }
+    assert!(!app.world().resource::<Assist>().showing());
+
+    app.world_mut().resource_mut::<PlayerInput>().assist = true;
+    app.update();
+    assert!(!app.world().resource::<Assist>().showing());
+}
+
+#[test]
+fn bleats_spread_out_and_echo() {
+    let mut app = start_run();
+    remove_other_sheep(&mut app);
+    app.world_mut().resource_mut::<BleatWaves>().echoes = 1;
+    let radius = |app: &mut App| {
+        let mut waves = app.world_mut().query::<&BleatWave>();
+        waves
+            .iter(app.world())
+            .find(|wave| wave.from_player)
+            .map(|wave| wave.radius)
+    };
+
+    app.world_mut().resource_mut::<PlayerInput>().bleat = true;
+    app.update();
+    app.world_mut().resource_mut::<PlayerInput>().bleat = false;
+    let mut last = radius(&mut app).expect("The player's bleat should send out a wave");
+
+    let mut echoes = 0;
+    for _ in 0..200 {
+        app.update();
+        let Some(now) = radius(&mut app) else {
+            break;
+        };
+        if now < last {
+            echoes += 1;
+        }
+        last = now;
+    }
+    assert_eq!(echoes, 1);
+    assert_eq!(radius(&mut app), None);
+}
+
+#[test]
+fn bleating_draws_the_wolves() {
+    let mut app = start_run();
+    remove_other_sheep(&mut app);
+    let position = player_position(&mut app);
+    app.world_mut().spawn((
+        Transform::from_translation(position + Vec3::new(250., 0., 0.)),
+        Wolf::default(),
+        Sprite::default(),
+    ));
+    app.update();
+    assert_eq!(wolf_state(&mut app).0, WolfState::Roam);
+
+    app.world_mut().resource_mut::<PlayerInput>().bleat = true;
+    app.update();
+    app.update();
+
+    let mut player = app.world_mut().query_filtered::<Entity, With<HumanMind>>();
+    let player = player.single(app.world()).unwrap();
+    let mut wolf = app.world_mut().query::<&Wolf>();
+    let wolf = wolf.single(app.world()).unwrap();
+    assert_eq!(wolf.state(), WolfState::Stalk);
+    assert_eq!(wolf.prey(), Some(player));
+}
+
+#[test]
+fn the_players_sheep_bleating_on_its_own_does_not_gather_the_flock() {
+    let mut app = start_run();
+    let mut player = app
+        .world_mut()
+        .query_filtered::<(Entity, &Transform), With<HumanMind>>();
+    let (player, transf) = player.single(app.world()).unwrap();
+    let pos = transf.translation.xy();
+    let mut others = app
+        .world_mut()
+        .query_filtered::<Entity, (With<Sheep>, Without<HumanMind>)>();
+    let other = others.iter(app.world()).next().unwrap();
+
+    // Bleat right next to the player until its sheep bleats back
+    let mut waves = app.world_mut().query::<&BleatWave>();
+    for _ in 0..100 {
+        if waves.iter(app.world()).any(|wave| wave.source == player) {
+            break;
+        }
+        app.world_mut()
+            .spawn(BleatWave::new(other, pos + Vec2::X, false));
+        app.update();
+    }
+    let wave = waves
+        .iter(app.world())
+        .find(|wave| wave.source == player)
+        .unwrap();
+    assert!(!wave.from_player);
+
+    for _ in 0..40 {
+        app.update();
+    }
+    let mut minds = app.world_mut().query::<&SheepMind>();
+    assert!(minds.iter(app.world()).all(|mind| {
+        !matches!(mind.state, sheep::State::Moving { speed, .. } if (speed - GATHER_SPEED).abs() < 1e-6)
+    }));
+}
+
+#[test]
+fn boosting_is_faster_until_stamina_runs_out() {
+    let mut app = start_run();
+    remove_other_sheep(&mut app);
+    app.world_mut().resource_mut::<PlayerInput>().right = true;
+
+    // Wrapping around the screen would throw off the distances
+    let walk = |app: &mut App, ticks| {
+        let mut travelled = 0.;
+        for _ in 0..ticks {
+            let before = player_position(app);
+            app.update();
+            travelled += player_position(app).distance(before);
+        }
+        travelled
+    };
+    let normal = walk(&mut app, 32);
+    app.world_mut().resource_mut::<PlayerInput>().boost = true;
+    let boosted = walk(&mut app, 32);
+    assert!(boosted > 1.3 * normal, "{boosted} vs {normal}");
+
+    let stamina = app.world().resource::<Boost>().stamina;
+    assert!(stamina < 1.);
+    for _ in 0..128 {
+        if app.world().resource::<Boost>().stamina == 0. {
+            break;
+        }
+        app.update();
+    }
+    app.update();
+    assert!(!app.world().resource::<Boost>().active());
+}
+
+#[test]
+fn player_pushes_through_a_tight_cluster() {
+    let mut app = start_run();
+    remove_other_sheep(&mut app);
+    // Stay in the same sheep
+    app.world_mut()
+        .resource_mut::<JumpTimer>()
+        .timer
+        .set_duration(Duration::from_secs(60));
+
+    let mut player = app
+        .world_mut()
+        .query_filtered::<&mut Transform, With<HumanMind>>();
+    player.single_mut(app.world_mut()).unwrap().translation = Vec3::ZERO;
+    // As close as sheep can get to each other
+    for x in 0..3 {
+        for y in -1..=1 {
+            app.world_mut().spawn((
+                Sheep,
+                Transform::from_xyz(40. + 26. * x as f32, 26. * y as f32, 0.),
+            ));
+        }
+    }
+
+    app.world_mut().resource_mut::<PlayerInput>().right = true;
+    let mut last = player_position(&mut app).x;
+    for _ in 0..30 {
+        app.update();
+        let x = player_position(&mut app).x;
+        assert!(x >= last, "Pushed back from {last} to {x}");
+        last = x;
+    }
+    assert!(last > 40., "Didn't get into the cluster: {last}");
+}
+
+#[test]
+fn half_a_stick_push_walks_slower() {
+    let mut app = start_run();
+    let mut gamepad = Gamepad::default();
+    gamepad.analog_mut().set(GamepadAxis::LeftStickX, 0.6);
+    app.world_mut().spawn(gamepad);
+    app.update();
+    app.update();
+
+    let mut player = app.world_mut().query::<&HumanMind>();
+    let intent = player.single(app.world()).unwrap().intent;
+    assert!(intent.x > 0.3 && intent.x < 0.8, "{intent}");
+    assert_eq!(intent.y, 0.);
+}
+
+fn key(app: &mut App, key_code: KeyCode, state: ButtonState) {
+    app.world_mut().write_message(KeyboardInput {
+        key_code,
+        logical_key: Key::Unidentified(NativeKey::Unidentified),
+        state,
+        text: None,
+        repeat: false,
+        window: Entity::PLACEHOLDER,
+    });
+    app.update();
+}
+
+#[test]
+fn arrows_and_wasd_both_work() {
+    let mut app = start_run();
+    key(&mut app, KeyCode::KeyW, ButtonState::Pressed);
+    key(&mut app, KeyCode::ArrowUp, ButtonState::Pressed);
+    assert!(app.world().resource::<PlayerInput>().up);
+
+    // W is still down
+    key(&mut app, KeyCode::ArrowUp, ButtonState::Released);
+    assert!(app.world().resource::<PlayerInput>().up);
+
+    key(&mut app, KeyCode::KeyW, ButtonState::Released);
+    assert!(!app.world().resource::<PlayerInput>().up);
+
+    key(&mut app, KeyCode::ArrowLeft, ButtonState::Pressed);
+    assert!(app.world().resource::<PlayerInput>().left);
+}
+
+#[test]
+fn taps_between_ticks_are_not_missed() {
+    let mut app = start_run();
+    // Pressed and released within one frame, before any tick has seen it
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        app.world_mut().write_message(KeyboardInput {
+            key_code: KeyCode::Space,
+            logical_key: Key::Unidentified(NativeKey::Unidentified),
+            state,
+            text: None,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+    app.update();
+    assert!(app.world().resource::<PlayerInput>().bleat);
+    app.update();
+    assert!(!app.world().resource::<PlayerInput>().bleat);
+
+    let mut waves = app.world_mut().query::<&BleatWave>();
+    assert!(waves.iter(app.world()).any(|wave| wave.from_player));
+}
+
+/// Where every sheep and wolf is after `secs` of a run drawn at `fps`
+fn positions_after(fps: u32, secs: u64) -> Vec<Vec2> {
+    let mut app = start_run();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        Duration::from_secs(1) / fps,
+    ));
+    play(&mut app, secs);
+    positions(&mut app)
+}
+
+fn play(app: &mut App, secs: u64) {
+    let end = app.world().resource::<Time<Fixed>>().elapsed() + Duration::from_secs(secs);
+    while app.world().resource::<Time<Fixed>>().elapsed() < end {
+        app.update();
+    }
+}
+
+/// Where every sheep and wolf is, in a stable order
+fn positions(app: &mut App) -> Vec<Vec2> {
+    let mut query = app
+        .world_mut()
+        .query_filtered::<&Transform, Or<(With<Sheep>, With<Wolf>)>>();
+    let mut positions: Vec<Vec2> = query
+        .iter(app.world())
+        .map(|transf| transf.translation.truncate())
+        .collect();
+    positions.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    positions
+}
+
+#[test]
+fn frame_rate_does_not_change_the_run() {
+    // Both are whole multiples of the fixed tick, so both runs stop on the same tick
+    assert_eq!(positions_after(32, 6), positions_after(256, 6));
+}
+
+#[test]
+fn same_seed_replays_the_same_run() {
+    // Counted from the start of each run, the app's first update doesn't tick
+    let play = |app: &mut App| {
+        while app.world().resource::<DifficultySecs>().0 < 6. {
+            app.update();
+        }
+        positions(app)
+    };
+    let mut app = start_run();
+    let first = play(&mut app);
+
+    // A second run in the same session
+    app.world_mut()
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Title);
+    app.update();
+    app.world_mut().resource_mut::<GameRng>().next_seed = Some(42);
+    app.world_mut()
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Gameplay);
+    app.update();
+
+    assert_eq!(play(&mut app), first);
+}